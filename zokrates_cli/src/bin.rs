@@ -11,7 +11,7 @@ use std::fs::File;
 use std::io::{stdin, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::string::String;
-use zokrates_core::compile::compile;
+use zokrates_core::compile::{compile, compile_with_warnings};
 use zokrates_core::ir;
 use zokrates_core::proof_system::*;
 use zokrates_field::field::{Field, FieldPrime};
@@ -227,10 +227,14 @@ fn cli() -> Result<(), String> {
 
             let mut reader = BufReader::new(file);
 
-            let program_flattened: ir::Prog<FieldPrime> =
-                compile(&mut reader, Some(location), Some(fs_resolve))
+            let (program_flattened, warnings): (ir::Prog<FieldPrime>, _) =
+                compile_with_warnings(&mut reader, Some(location), Some(fs_resolve))
                     .map_err(|e| format!("Compilation failed:\n\n {}", e))?;
 
+            for warning in warnings {
+                eprintln!("WARNING: {}", warning);
+            }
+
             // number of constraints the flattened program will translate to.
             let num_constraints = program_flattened.constraint_count();
 
@@ -380,7 +384,9 @@ fn cli() -> Result<(), String> {
                     .map_err(|why| format!("couldn't open {}: {}", input_path.display(), why))?;
                 let reader = BufReader::new(input_file);
 
-                let verifier = scheme.export_solidity_verifier(reader);
+                let verifier = scheme
+                    .export_solidity_verifier(reader)
+                    .map_err(|e| format!("couldn't export verifier: {}", e))?;
 
                 //write output file
                 let output_path = Path::new(sub_matches.value_of("output").unwrap());
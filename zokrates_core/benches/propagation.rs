@@ -0,0 +1,83 @@
+#![feature(test)]
+
+extern crate test;
+extern crate zokrates_core;
+extern crate zokrates_field;
+
+use std::io;
+use test::Bencher;
+use zokrates_core::compile::compile;
+use zokrates_core::ir;
+use zokrates_field::field::FieldPrime;
+
+// a loop which unrolls to many definitions, the constant ones of which propagation folds away
+const UNROLLED: &str = "
+def main(field x) -> (field):
+    field c = 0
+    field acc = x
+    for field i in 0..200 do
+        c = c + i
+        acc = acc + c * x
+    endfor
+    return acc
+";
+
+// a constant array of `n` elements updated one element at a time, each update copying the array in propagation, and
+// read by a straight line of `n` definitions once unrolled
+fn array_updates(n: usize) -> String {
+    let values: Vec<_> = (0..n).map(|i| i.to_string()).collect();
+
+    format!(
+        "
+def main(field x) -> (field):
+    field[{n}] a = [{values}]
+    field acc = x
+    for field i in 0..{n} do
+        a[i] = a[i] + 1
+        acc = acc + a[i] * x
+    endfor
+    return acc
+",
+        n = n,
+        values = values.join(", ")
+    )
+}
+
+fn compile_source(source: &str) -> ir::Prog<FieldPrime> {
+    compile::<FieldPrime, &[u8], &[u8], io::Error>(&mut source.as_bytes(), None, None).unwrap()
+}
+
+fn compile_unrolled() -> ir::Prog<FieldPrime> {
+    compile_source(UNROLLED)
+}
+
+#[bench]
+fn propagate_unrolled(b: &mut Bencher) {
+    let expected = compile_unrolled().to_string();
+
+    b.iter(|| compile_unrolled());
+
+    // propagation is deterministic, so every run compiles to the same program
+    assert_eq!(compile_unrolled().to_string(), expected);
+}
+
+fn bench_array_updates(b: &mut Bencher, n: usize) {
+    let source = array_updates(n);
+
+    b.iter(|| compile_source(&source));
+}
+
+#[bench]
+fn propagate_array_updates_10(b: &mut Bencher) {
+    bench_array_updates(b, 10);
+}
+
+#[bench]
+fn propagate_array_updates_50(b: &mut Bencher) {
+    bench_array_updates(b, 50);
+}
+
+#[bench]
+fn propagate_array_updates_100(b: &mut Bencher) {
+    bench_array_updates(b, 100);
+}
@@ -192,6 +192,7 @@ impl<'ast, T: Field> From<pest::AssertionStatement<'ast>> for absy::StatementNod
                 pest::BinaryOperator::Eq => absy::Statement::Condition(
                     absy::ExpressionNode::from(*e.left),
                     absy::ExpressionNode::from(*e.right),
+                    statement.message.map(|m| m.value),
                 ),
                 _ => unimplemented!(
                     "Assertion statements should be an equality check, found {}",
@@ -258,6 +259,8 @@ impl<'ast, T: Field> From<pest::Expression<'ast>> for absy::ExpressionNode<'ast,
             pest::Expression::Postfix(e) => absy::ExpressionNode::from(e),
             pest::Expression::InlineArray(e) => absy::ExpressionNode::from(e),
             pest::Expression::Unary(e) => absy::ExpressionNode::from(e),
+            pest::Expression::Cast(e) => absy::ExpressionNode::from(e),
+            pest::Expression::Builtin(e) => absy::ExpressionNode::from(e),
         }
     }
 }
@@ -314,6 +317,10 @@ impl<'ast, T: Field> From<pest::BinaryExpression<'ast>> for absy::ExpressionNode
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
             ),
+            pest::BinaryOperator::Concat => absy::Expression::Concat(
+                box absy::ExpressionNode::from(*expression.left),
+                box absy::ExpressionNode::from(*expression.right),
+            ),
             o => unimplemented!("Operator {:?} not implemented", o),
         }
         .span(expression.span)
@@ -359,11 +366,38 @@ impl<'ast, T: Field> From<pest::UnaryExpression<'ast>> for absy::ExpressionNode<
     }
 }
 
+impl<'ast, T: Field> From<pest::CastExpression<'ast>> for absy::ExpressionNode<'ast, T> {
+    fn from(cast: pest::CastExpression<'ast>) -> absy::ExpressionNode<'ast, T> {
+        use absy::NodeValue;
+
+        let expression = box absy::ExpressionNode::from(*cast.expression);
+        match cast.ty {
+            pest::BasicType::Field(_) => absy::Expression::FromBoolean(expression),
+            pest::BasicType::Boolean(_) => absy::Expression::FromField(expression),
+        }
+        .span(cast.span)
+    }
+}
+
+impl<'ast, T: Field> From<pest::BuiltinExpression<'ast>> for absy::ExpressionNode<'ast, T> {
+    fn from(expression: pest::BuiltinExpression<'ast>) -> absy::ExpressionNode<'ast, T> {
+        use absy::NodeValue;
+
+        let left = box absy::ExpressionNode::from(*expression.left);
+        let right = box absy::ExpressionNode::from(*expression.right);
+        match expression.builtin {
+            pest::Builtin::Max(_) => absy::Expression::Max(left, right),
+            pest::Builtin::Min(_) => absy::Expression::Min(left, right),
+        }
+        .span(expression.span)
+    }
+}
+
 impl<'ast, T: Field> From<pest::PostfixExpression<'ast>> for absy::ExpressionNode<'ast, T> {
     fn from(expression: pest::PostfixExpression<'ast>) -> absy::ExpressionNode<'ast, T> {
         use absy::NodeValue;
 
-        assert!(expression.access.len() == 1); // we only allow a single access: function call or array access
+        assert!(expression.access.len() == 1); // we only allow a single access: function call, array access or slice
 
         match expression.access[0].clone() {
             pest::Access::Call(a) => absy::Expression::FunctionCall(
@@ -377,6 +411,11 @@ impl<'ast, T: Field> From<pest::PostfixExpression<'ast>> for absy::ExpressionNod
                 box absy::ExpressionNode::from(expression.id),
                 box absy::ExpressionNode::from(a.expression),
             ),
+            pest::Access::Slice(a) => absy::Expression::Slice(
+                box absy::ExpressionNode::from(expression.id),
+                box absy::ExpressionNode::from(a.from),
+                box absy::ExpressionNode::from(a.to),
+            ),
         }
         .span(expression.span)
     }
@@ -512,4 +551,193 @@ mod tests {
 
         assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
     }
+
+    #[test]
+    fn cast() {
+        let source = "def main(bool b) -> (field): return field(b)
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: &source[4..8],
+                arguments: vec![absy::Parameter::public(
+                    absy::Variable::boolean(&source[14..15]).into(),
+                )
+                .into()],
+                statements: vec![absy::Statement::Return(
+                    absy::ExpressionList {
+                        expressions: vec![absy::Expression::FromBoolean(
+                            box absy::Expression::Identifier(&source[42..43]).into(),
+                        )
+                        .into()],
+                    }
+                    .into(),
+                )
+                .into()],
+                signature: absy::Signature::new()
+                    .inputs(vec![Type::Boolean])
+                    .outputs(vec![Type::FieldElement]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn concat() {
+        let source = "def main(field[2] a, field[1] b) -> (field[3]): return a ++ b
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: &source[4..8],
+                arguments: vec![
+                    absy::Parameter::public(absy::Variable::field_array(&source[18..19], 2).into())
+                        .into(),
+                    absy::Parameter::public(absy::Variable::field_array(&source[30..31], 1).into())
+                        .into(),
+                ],
+                statements: vec![absy::Statement::Return(
+                    absy::ExpressionList {
+                        expressions: vec![absy::Expression::Concat(
+                            box absy::Expression::Identifier(&source[55..56]).into(),
+                            box absy::Expression::Identifier(&source[59..60]).into(),
+                        )
+                        .into()],
+                    }
+                    .into(),
+                )
+                .into()],
+                signature: absy::Signature::new()
+                    .inputs(vec![Type::FieldElementArray(2), Type::FieldElementArray(1)])
+                    .outputs(vec![Type::FieldElementArray(3)]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn slice() {
+        let source = "def main(field[4] a) -> (field[2]): return a[1..3]
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: &source[4..8],
+                arguments: vec![absy::Parameter::public(
+                    absy::Variable::field_array(&source[18..19], 4).into(),
+                )
+                .into()],
+                statements: vec![absy::Statement::Return(
+                    absy::ExpressionList {
+                        expressions: vec![absy::Expression::Slice(
+                            box absy::Expression::Identifier(&source[43..44]).into(),
+                            box absy::Expression::Number(FieldPrime::from(1)).into(),
+                            box absy::Expression::Number(FieldPrime::from(3)).into(),
+                        )
+                        .into()],
+                    }
+                    .into(),
+                )
+                .into()],
+                signature: absy::Signature::new()
+                    .inputs(vec![Type::FieldElementArray(4)])
+                    .outputs(vec![Type::FieldElementArray(2)]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn min() {
+        let source = "def main(field a, field b) -> (field): return min(a, b)
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: &source[4..8],
+                arguments: vec![
+                    absy::Parameter::public(absy::Variable::field_element(&source[15..16]).into())
+                        .into(),
+                    absy::Parameter::public(absy::Variable::field_element(&source[24..25]).into())
+                        .into(),
+                ],
+                statements: vec![absy::Statement::Return(
+                    absy::ExpressionList {
+                        expressions: vec![absy::Expression::Min(
+                            box absy::Expression::Identifier(&source[50..51]).into(),
+                            box absy::Expression::Identifier(&source[53..54]).into(),
+                        )
+                        .into()],
+                    }
+                    .into(),
+                )
+                .into()],
+                signature: absy::Signature::new()
+                    .inputs(vec![Type::FieldElement, Type::FieldElement])
+                    .outputs(vec![Type::FieldElement]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn assertion_message() {
+        let source = "def main() -> (field):
+        assert(1 == 1, \"one is one\")
+        return 42
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: &source[4..8],
+                arguments: vec![],
+                statements: vec![
+                    absy::Statement::Condition(
+                        absy::Expression::Number(FieldPrime::from(1)).into(),
+                        absy::Expression::Number(FieldPrime::from(1)).into(),
+                        Some(String::from("one is one")),
+                    )
+                    .into(),
+                    absy::Statement::Return(
+                        absy::ExpressionList {
+                            expressions: vec![
+                                absy::Expression::Number(FieldPrime::from(42)).into()
+                            ],
+                        }
+                        .into(),
+                    )
+                    .into(),
+                ],
+                signature: absy::Signature::new()
+                    .inputs(vec![])
+                    .outputs(vec![Type::FieldElement]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
 }
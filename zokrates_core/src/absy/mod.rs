@@ -157,7 +157,7 @@ pub enum Statement<'ast, T: Field> {
     Return(ExpressionListNode<'ast, T>),
     Declaration(VariableNode<'ast>),
     Definition(AssigneeNode<'ast, T>, ExpressionNode<'ast, T>),
-    Condition(ExpressionNode<'ast, T>, ExpressionNode<'ast, T>),
+    Condition(ExpressionNode<'ast, T>, ExpressionNode<'ast, T>, Option<String>),
     For(VariableNode<'ast>, T, T, Vec<StatementNode<'ast, T>>),
     MultipleDefinition(Vec<AssigneeNode<'ast, T>>, ExpressionNode<'ast, T>),
 }
@@ -170,7 +170,10 @@ impl<'ast, T: Field> fmt::Display for Statement<'ast, T> {
             Statement::Return(ref expr) => write!(f, "return {}", expr),
             Statement::Declaration(ref var) => write!(f, "{}", var),
             Statement::Definition(ref lhs, ref rhs) => write!(f, "{} = {}", lhs, rhs),
-            Statement::Condition(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            Statement::Condition(ref lhs, ref rhs, ref message) => match message {
+                Some(message) => write!(f, "assert({} == {}, \"{}\")", lhs, rhs, message),
+                None => write!(f, "{} == {}", lhs, rhs),
+            },
             Statement::For(ref var, ref start, ref stop, ref list) => {
                 r#try!(write!(f, "for {} in {}..{} do\n", var, start, stop));
                 for l in list {
@@ -199,7 +202,9 @@ impl<'ast, T: Field> fmt::Debug for Statement<'ast, T> {
             Statement::Definition(ref lhs, ref rhs) => {
                 write!(f, "Definition({:?}, {:?})", lhs, rhs)
             }
-            Statement::Condition(ref lhs, ref rhs) => write!(f, "Condition({:?}, {:?})", lhs, rhs),
+            Statement::Condition(ref lhs, ref rhs, ref message) => {
+                write!(f, "Condition({:?}, {:?}, {:?})", lhs, rhs, message)
+            }
             Statement::For(ref var, ref start, ref stop, ref list) => {
                 r#try!(write!(f, "for {:?} in {:?}..{:?} do\n", var, start, stop));
                 for l in list {
@@ -223,6 +228,8 @@ pub enum Expression<'ast, T: Field> {
     Mult(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Div(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Pow(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    Max(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    Min(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     IfElse(
         Box<ExpressionNode<'ast, T>>,
         Box<ExpressionNode<'ast, T>>,
@@ -239,6 +246,14 @@ pub enum Expression<'ast, T: Field> {
     InlineArray(Vec<ExpressionNode<'ast, T>>),
     Select(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Or(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    FromBoolean(Box<ExpressionNode<'ast, T>>),
+    FromField(Box<ExpressionNode<'ast, T>>),
+    Concat(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    Slice(
+        Box<ExpressionNode<'ast, T>>,
+        Box<ExpressionNode<'ast, T>>,
+        Box<ExpressionNode<'ast, T>>,
+    ),
 }
 
 pub type ExpressionNode<'ast, T> = Node<Expression<'ast, T>>;
@@ -253,6 +268,8 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
             Expression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
             Expression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
             Expression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
+            Expression::Max(ref lhs, ref rhs) => write!(f, "max({}, {})", lhs, rhs),
+            Expression::Min(ref lhs, ref rhs) => write!(f, "min({}, {})", lhs, rhs),
             Expression::IfElse(ref condition, ref consequent, ref alternative) => write!(
                 f,
                 "if {} then {} else {} fi",
@@ -287,6 +304,12 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
             }
             Expression::Select(ref array, ref index) => write!(f, "{}[{}]", array, index),
             Expression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
+            Expression::FromBoolean(ref exp) => write!(f, "field({})", exp),
+            Expression::FromField(ref exp) => write!(f, "bool({})", exp),
+            Expression::Concat(ref lhs, ref rhs) => write!(f, "({} ++ {})", lhs, rhs),
+            Expression::Slice(ref array, ref from, ref to) => {
+                write!(f, "{}[{}..{}]", array, from, to)
+            }
         }
     }
 }
@@ -294,13 +317,15 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
 impl<'ast, T: Field> fmt::Debug for Expression<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Expression::Number(ref i) => write!(f, "Num({})", i),
+            Expression::Number(ref i) => write!(f, "Num({})", i.to_hex_string()),
             Expression::Identifier(ref var) => write!(f, "Ide({})", var),
             Expression::Add(ref lhs, ref rhs) => write!(f, "Add({:?}, {:?})", lhs, rhs),
             Expression::Sub(ref lhs, ref rhs) => write!(f, "Sub({:?}, {:?})", lhs, rhs),
             Expression::Mult(ref lhs, ref rhs) => write!(f, "Mult({:?}, {:?})", lhs, rhs),
             Expression::Div(ref lhs, ref rhs) => write!(f, "Div({:?}, {:?})", lhs, rhs),
             Expression::Pow(ref lhs, ref rhs) => write!(f, "Pow({:?}, {:?})", lhs, rhs),
+            Expression::Max(ref lhs, ref rhs) => write!(f, "Max({:?}, {:?})", lhs, rhs),
+            Expression::Min(ref lhs, ref rhs) => write!(f, "Min({:?}, {:?})", lhs, rhs),
             Expression::IfElse(ref condition, ref consequent, ref alternative) => write!(
                 f,
                 "IfElse({:?}, {:?}, {:?})",
@@ -325,6 +350,12 @@ impl<'ast, T: Field> fmt::Debug for Expression<'ast, T> {
             }
             Expression::Select(ref array, ref index) => write!(f, "{}[{}]", array, index),
             Expression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
+            Expression::FromBoolean(ref exp) => write!(f, "FromBoolean({:?})", exp),
+            Expression::FromField(ref exp) => write!(f, "FromField({:?})", exp),
+            Expression::Concat(ref lhs, ref rhs) => write!(f, "Concat({:?}, {:?})", lhs, rhs),
+            Expression::Slice(ref array, ref from, ref to) => {
+                write!(f, "Slice({:?}, {:?}, {:?})", array, from, to)
+            }
         }
     }
 }
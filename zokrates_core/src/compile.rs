@@ -10,7 +10,7 @@ use imports::{self, Importer};
 use ir;
 use optimizer::Optimize;
 use semantics::{self, Checker};
-use static_analysis::Analyse;
+use static_analysis::{Analyse, PropagationError, Warning};
 use std::fmt;
 use std::io;
 use std::io::BufRead;
@@ -46,6 +46,7 @@ pub enum CompileErrorInner {
     ImportError(imports::Error),
     SemanticError(semantics::Error),
     ReadError(io::Error),
+    AnalysisError(PropagationError),
 }
 
 impl CompileErrorInner {
@@ -87,6 +88,23 @@ impl fmt::Display for CompileError {
     }
 }
 
+/// A warning found while compiling a file, which does not prevent its compilation
+#[derive(Debug, PartialEq)]
+pub struct CompileWarning {
+    context: Option<String>,
+    value: Warning,
+}
+
+impl fmt::Display for CompileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let context = match self.context {
+            Some(ref x) => x.clone(),
+            None => "???".to_string(),
+        };
+        write!(f, "{}:{}", context, self.value)
+    }
+}
+
 impl From<pest::Error> for CompileErrorInner {
     fn from(error: pest::Error) -> Self {
         CompileErrorInner::ParserError(error)
@@ -111,6 +129,12 @@ impl From<semantics::Error> for CompileErrorInner {
     }
 }
 
+impl From<PropagationError> for CompileErrorInner {
+    fn from(error: PropagationError) -> Self {
+        CompileErrorInner::AnalysisError(error)
+    }
+}
+
 impl fmt::Display for CompileErrorInner {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let res = match *self {
@@ -118,6 +142,7 @@ impl fmt::Display for CompileErrorInner {
             CompileErrorInner::SemanticError(ref e) => format!("{}", e),
             CompileErrorInner::ReadError(ref e) => format!("{}", e),
             CompileErrorInner::ImportError(ref e) => format!("{}", e),
+            CompileErrorInner::AnalysisError(ref e) => format!("{}", e),
         };
         write!(f, "{}", res)
     }
@@ -128,22 +153,31 @@ pub fn compile<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     location: Option<String>,
     resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
 ) -> Result<ir::Prog<T>, CompileErrors> {
-    let compiled = compile_aux(reader, location, resolve_option)?;
-    Ok(ir::Prog::from(compiled).optimize())
+    compile_with_warnings(reader, location, resolve_option).map(|(program, _)| program)
+}
+
+/// Compiles like `compile`, also returning the warnings found in the program and its imports
+pub fn compile_with_warnings<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Result<(ir::Prog<T>, Vec<CompileWarning>), CompileErrors> {
+    let (compiled, warnings) = compile_aux(reader, location, resolve_option)?;
+    Ok((ir::Prog::from(compiled).optimize(), warnings))
 }
 
 pub fn compile_aux<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     reader: &mut R,
     location: Option<String>,
     resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
-) -> Result<FlatProg<T>, CompileErrors> {
+) -> Result<(FlatProg<T>, Vec<CompileWarning>), CompileErrors> {
     let mut source = String::new();
     reader.read_to_string(&mut source).unwrap();
     let ast = pest::generate_ast(&source)
         .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
     let program_ast_without_imports: Prog<T> = Prog::from(ast);
 
-    let program_ast = Importer::new().apply_imports(
+    let (program_ast, mut warnings) = Importer::new().apply_imports(
         program_ast_without_imports,
         location.clone(),
         resolve_option,
@@ -160,15 +194,23 @@ pub fn compile_aux<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     })?;
 
     // analyse (unroll and constant propagation)
-    let typed_ast = typed_ast.analyse();
+    let (typed_ast, analysis_warnings) = typed_ast
+        .analyse()
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    warnings.extend(analysis_warnings.into_iter().map(|value| CompileWarning {
+        context: location.clone(),
+        value,
+    }));
 
     // flatten input program
     let program_flattened = Flattener::flatten(typed_ast);
 
     // analyse (constant propagation after call resolution)
-    let program_flattened = program_flattened.analyse();
+    let (program_flattened, _) = program_flattened
+        .analyse()
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
 
-    Ok(program_flattened)
+    Ok((program_flattened, warnings))
 }
 
 #[cfg(test)]
@@ -225,4 +267,61 @@ mod test {
         );
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn unsatisfiable_assertion() {
+        let mut r = BufReader::new(
+            r#"
+			def main() -> (field):
+			   1 == 2
+			   return 1
+		"#
+            .as_bytes(),
+        );
+        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        );
+
+        // the error is reported at the line of the assertion
+        let error = res.unwrap_err().to_string();
+        assert!(error.starts_with("./path/to/file:3:"));
+        assert!(error.contains(&"assertion 1 == 2 can never be satisfied"));
+    }
+
+    #[test]
+    fn unused_parameter_warning() {
+        let mut r = BufReader::new(
+            r#"
+			def main(field a) -> (field):
+			   return 1
+		"#
+            .as_bytes(),
+        );
+        let (_, warnings): (ir::Prog<FieldPrime>, _) = compile_with_warnings(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        )
+        .unwrap();
+
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>(),
+            vec!["./path/to/file:parameter `a` of function `main` is never used"]
+        );
+    }
 }
@@ -60,7 +60,7 @@ impl<T: Field> fmt::Debug for FlatProg<T> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct FlatFunction<T: Field> {
     /// Name of the program
     pub id: String,
@@ -169,7 +169,7 @@ impl<T: Field> fmt::Debug for FlatFunction<T> {
 ///
 /// * r1cs - R1CS in standard JSON data format
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum FlatStatement<T: Field> {
     Return(FlatExpressionList<T>),
     Condition(FlatExpression<T>, FlatExpression<T>),
@@ -316,7 +316,7 @@ impl<T: Field> fmt::Display for FlatExpression<T> {
 impl<T: Field> fmt::Debug for FlatExpression<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            FlatExpression::Number(ref i) => write!(f, "Num({})", i),
+            FlatExpression::Number(ref i) => write!(f, "Num({})", i.to_hex_string()),
             FlatExpression::Identifier(ref var) => write!(f, "Ide({})", var),
             FlatExpression::Add(ref lhs, ref rhs) => write!(f, "Add({:?}, {:?})", lhs, rhs),
             FlatExpression::Sub(ref lhs, ref rhs) => write!(f, "Sub({:?}, {:?})", lhs, rhs),
@@ -79,21 +79,29 @@ impl<'ast> Flattener<'ast> {
                         vec![BooleanExpression::Identifier("condition".into()).into()],
                     )
                     .into(),
+                    None,
                 ),
-                TypedStatement::Return(vec![FieldElementExpression::Add(
-                    box FieldElementExpression::Mult(
-                        box FieldElementExpression::Identifier("condition_as_field".into()),
-                        box FieldElementExpression::Identifier("consequence".into()),
-                    ),
-                    box FieldElementExpression::Mult(
-                        box FieldElementExpression::Sub(
-                            box FieldElementExpression::Number(T::one()),
-                            box FieldElementExpression::Identifier("condition_as_field".into()),
+                TypedStatement::Return(
+                    vec![FieldElementExpression::Add(
+                        box FieldElementExpression::Mult(
+                            box FieldElementExpression::Identifier(
+                                "condition_as_field".into(),
+                            ),
+                            box FieldElementExpression::Identifier("consequence".into()),
                         ),
-                        box FieldElementExpression::Identifier("alternative".into()),
-                    ),
-                )
-                .into()]),
+                        box FieldElementExpression::Mult(
+                            box FieldElementExpression::Sub(
+                                box FieldElementExpression::Number(T::one()),
+                                box FieldElementExpression::Identifier(
+                                    "condition_as_field".into(),
+                                ),
+                            ),
+                            box FieldElementExpression::Identifier("alternative".into()),
+                        ),
+                    )
+                    .into()],
+                    None,
+                ),
             ],
             signature: Signature::new()
                 .inputs(vec![Type::Boolean, Type::FieldElement, Type::FieldElement])
@@ -128,7 +136,7 @@ impl<'ast> Flattener<'ast> {
             }
             BooleanExpression::Lt(box lhs, box rhs) => {
                 // Get the bitwidth to know the size of the binary decompsitions for this Field
-                let bitwidth = T::get_required_bits();
+                let bitwidth = T::bits();
 
                 // We know from semantic checking that lhs and rhs have the same type
                 // What the expression will flatten to depends on that type
@@ -386,15 +394,118 @@ impl<'ast> Flattener<'ast> {
 
                 FlatExpression::Identifier(name_x_and_y)
             }
+            BooleanExpression::BoolEq(box lhs, box rhs) => {
+                // for booleans, x == y is 1 - (x - y)^2
+                let x =
+                    self.flatten_boolean_expression(functions_flattened, statements_flattened, lhs);
+                let y =
+                    self.flatten_boolean_expression(functions_flattened, statements_flattened, rhs);
+                assert!(x.is_linear() && y.is_linear());
+
+                let name_x_minus_y_squared = self.use_sym();
+                let x_minus_y = FlatExpression::Sub(box x, box y);
+                statements_flattened.push(FlatStatement::Definition(
+                    name_x_minus_y_squared,
+                    FlatExpression::Mult(box x_minus_y.clone(), box x_minus_y),
+                ));
+
+                FlatExpression::Sub(
+                    box FlatExpression::Number(T::one()),
+                    box FlatExpression::Identifier(name_x_minus_y_squared),
+                )
+            }
             BooleanExpression::Not(box exp) => {
                 let x =
                     self.flatten_boolean_expression(functions_flattened, statements_flattened, exp);
                 FlatExpression::Sub(box FlatExpression::Number(T::one()), box x)
             }
+            BooleanExpression::ArrayEq(box lhs, box rhs) => {
+                // a == b is a[0] == b[0] && ... && a[n - 1] == b[n - 1]
+                assert_eq!(lhs.size(), rhs.size());
+                let mut eqs = (0..lhs.size()).map(|i| {
+                    BooleanExpression::Eq(
+                        box FieldElementExpression::Select(
+                            box lhs.clone(),
+                            box FieldElementExpression::Number(T::from(i)),
+                        ),
+                        box FieldElementExpression::Select(
+                            box rhs.clone(),
+                            box FieldElementExpression::Number(T::from(i)),
+                        ),
+                    )
+                });
+                // empty arrays are always equal
+                let eq = match eqs.next() {
+                    Some(first) => eqs.fold(first, |acc, e| BooleanExpression::And(box acc, box e)),
+                    None => BooleanExpression::Value(true),
+                };
+
+                self.flatten_boolean_expression(functions_flattened, statements_flattened, eq)
+            }
+            BooleanExpression::FromField(box exp) => {
+                // x is 0 or 1 iff x == x * x
+                let x = self.flatten_field_expression(functions_flattened, statements_flattened, exp);
+                let x = match x.is_linear() {
+                    true => x,
+                    false => {
+                        let name_x = self.use_sym();
+                        statements_flattened.push(FlatStatement::Definition(name_x, x));
+                        FlatExpression::Identifier(name_x)
+                    }
+                };
+
+                statements_flattened.push(FlatStatement::Condition(
+                    x.clone(),
+                    FlatExpression::Mult(box x.clone(), box x.clone()),
+                ));
+
+                x
+            }
+            BooleanExpression::FunctionCall(ref id, ref param_expressions) => {
+                let exprs_flattened = self.flatten_function_call(
+                    functions_flattened,
+                    statements_flattened,
+                    id,
+                    vec![Type::Boolean],
+                    param_expressions,
+                );
+                assert!(exprs_flattened.expressions.len() == 1); // outside of MultipleDefinition, FunctionCalls must return a single value
+                exprs_flattened.expressions[0].clone()
+            }
             BooleanExpression::Value(b) => FlatExpression::Number(match b {
                 true => T::from(1),
                 false => T::from(0),
             }),
+            BooleanExpression::IfElse(box condition, box consequence, box alternative) => {
+                // if c then x else y == c * (x - y) + y
+                let c = self.flatten_boolean_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    condition,
+                );
+                let x = self.flatten_boolean_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    consequence,
+                );
+                let y = self.flatten_boolean_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    alternative,
+                );
+                assert!(c.is_linear() && x.is_linear() && y.is_linear());
+
+                let name_c_times_x_minus_y = self.use_sym();
+                statements_flattened.push(FlatStatement::Definition(
+                    name_c_times_x_minus_y,
+                    FlatExpression::Mult(box c, box FlatExpression::Sub(box x, box y.clone())),
+                ));
+
+                FlatExpression::Add(
+                    box y,
+                    box FlatExpression::Identifier(name_c_times_x_minus_y),
+                )
+            }
         }
     }
 
@@ -576,6 +687,20 @@ impl<'ast> Flattener<'ast> {
 
                 FlatExpression::Sub(box new_left, box new_right)
             }
+            FieldElementExpression::Neg(box e) => {
+                let e_flattened =
+                    self.flatten_field_expression(functions_flattened, statements_flattened, e);
+
+                let new_e = if e_flattened.is_linear() {
+                    e_flattened
+                } else {
+                    let id = self.use_sym();
+                    statements_flattened.push(FlatStatement::Definition(id, e_flattened));
+                    FlatExpression::Identifier(id)
+                };
+
+                FlatExpression::Sub(box FlatExpression::Number(T::from(0)), box new_e)
+            }
             FieldElementExpression::Mult(box left, box right) => {
                 let left_flattened =
                     self.flatten_field_expression(functions_flattened, statements_flattened, left);
@@ -695,6 +820,25 @@ impl<'ast> Flattener<'ast> {
                     _ => panic!("Expected number as pow exponent"),
                 }
             }
+            // `max` and `min` are lowered to the conditional they stand for
+            FieldElementExpression::Max(box e1, box e2) => self.flatten_field_expression(
+                functions_flattened,
+                statements_flattened,
+                FieldElementExpression::IfElse(
+                    box BooleanExpression::Gt(box e1.clone(), box e2.clone()),
+                    box e1,
+                    box e2,
+                ),
+            ),
+            FieldElementExpression::Min(box e1, box e2) => self.flatten_field_expression(
+                functions_flattened,
+                statements_flattened,
+                FieldElementExpression::IfElse(
+                    box BooleanExpression::Lt(box e1.clone(), box e2.clone()),
+                    box e1,
+                    box e2,
+                ),
+            ),
             FieldElementExpression::IfElse(box condition, box consequent, box alternative) => self
                 .flatten_function_call(
                     functions_flattened,
@@ -716,6 +860,38 @@ impl<'ast> Flattener<'ast> {
                 assert!(exprs_flattened.expressions.len() == 1); // outside of MultipleDefinition, FunctionCalls must return a single value
                 exprs_flattened.expressions[0].clone()
             }
+            FieldElementExpression::SelectConstant(values, box index) => {
+                let size = values.len();
+                // the index is not constant, check that it is in 0..values.len()
+                self.flatten_index_range_check(
+                    functions_flattened,
+                    statements_flattened,
+                    index.clone(),
+                    size,
+                );
+
+                // as the values are constants, gather them with sum(if index == i then values[i] else 0)
+                // entries equal to zero do not contribute and are skipped
+                let lookup = values
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, v)| *v != T::from(0))
+                    .map(|(i, v)| {
+                        FieldElementExpression::IfElse(
+                            box BooleanExpression::Eq(
+                                box index.clone(),
+                                box FieldElementExpression::Number(T::from(i)),
+                            ),
+                            box FieldElementExpression::Number(v),
+                            box FieldElementExpression::Number(T::from(0)),
+                        )
+                    })
+                    .fold(FieldElementExpression::Number(T::from(0)), |acc, e| {
+                        FieldElementExpression::Add(box acc, box e)
+                    });
+
+                self.flatten_field_expression(functions_flattened, statements_flattened, lookup)
+            }
             FieldElementExpression::Select(box array, box index) => {
                 match index {
                     FieldElementExpression::Number(n) => match array {
@@ -759,36 +935,62 @@ impl<'ast> Flattener<'ast> {
                                 ),
                             )
                         }
+                        FieldElementArrayExpression::Repeat(size, box e) => {
+                            assert!(n < T::from(size));
+                            self.flatten_field_expression(
+                                functions_flattened,
+                                statements_flattened,
+                                e,
+                            )
+                        }
+                        FieldElementArrayExpression::Concat(box left, box right) => {
+                            // [a, b] ++ [c, d] selected at 2 is [c, d][0]
+                            let select = match n < T::from(left.size()) {
+                                true => FieldElementExpression::Select(
+                                    box left,
+                                    box FieldElementExpression::Number(n),
+                                ),
+                                false => FieldElementExpression::Select(
+                                    box right,
+                                    box FieldElementExpression::Number(n - T::from(left.size())),
+                                ),
+                            };
+                            self.flatten_field_expression(
+                                functions_flattened,
+                                statements_flattened,
+                                select,
+                            )
+                        }
+                        FieldElementArrayExpression::Slice(
+                            _,
+                            box array,
+                            box FieldElementExpression::Number(from),
+                            box FieldElementExpression::Number(to),
+                        ) => {
+                            // a[1..3] selected at 1 is a[2]
+                            assert!(from.clone() + &n < to);
+                            self.flatten_field_expression(
+                                functions_flattened,
+                                statements_flattened,
+                                FieldElementExpression::Select(
+                                    box array,
+                                    box FieldElementExpression::Number(from + n),
+                                ),
+                            )
+                        }
+                        FieldElementArrayExpression::Slice(..) => {
+                            unreachable!("slice bounds should be numbers")
+                        }
                     },
                     e => {
                         let size = array.size();
                         // we have array[e] with e an arbitrary expression
-                        // first we check that e is in 0..array.len(), so we check that sum(if e == i then 1 else 0) == 1
-                        // here depending on the size, we could use a proper range check based on bits
-                        let range_check = (0..size)
-                            .map(|i| {
-                                FieldElementExpression::IfElse(
-                                    box BooleanExpression::Eq(
-                                        box e.clone(),
-                                        box FieldElementExpression::Number(T::from(i)),
-                                    ),
-                                    box FieldElementExpression::Number(T::from(1)),
-                                    box FieldElementExpression::Number(T::from(0)),
-                                )
-                            })
-                            .fold(FieldElementExpression::Number(T::from(0)), |acc, e| {
-                                FieldElementExpression::Add(box acc, box e)
-                            });
-
-                        let range_check_statement = TypedStatement::Condition(
-                            FieldElementExpression::Number(T::from(1)).into(),
-                            range_check.into(),
-                        );
-
-                        self.flatten_statement(
+                        // first we check that e is in 0..array.len()
+                        self.flatten_index_range_check(
                             functions_flattened,
                             statements_flattened,
-                            range_check_statement,
+                            e.clone(),
+                            size,
                         );
 
                         // now we flatten to sum(if e == i then array[i] else 0)
@@ -832,6 +1034,34 @@ impl<'ast> Flattener<'ast> {
                                                 box FieldElementExpression::Number(T::from(i)),
                                             ),
                                         ),
+                                        FieldElementArrayExpression::Repeat(_, box e) => e,
+                                        FieldElementArrayExpression::Concat(
+                                            box left,
+                                            box right,
+                                        ) => match i < left.size() {
+                                            true => FieldElementExpression::Select(
+                                                box left,
+                                                box FieldElementExpression::Number(T::from(i)),
+                                            ),
+                                            false => FieldElementExpression::Select(
+                                                box right,
+                                                box FieldElementExpression::Number(T::from(
+                                                    i - left.size(),
+                                                )),
+                                            ),
+                                        },
+                                        FieldElementArrayExpression::Slice(
+                                            _,
+                                            box array,
+                                            box FieldElementExpression::Number(from),
+                                            _,
+                                        ) => FieldElementExpression::Select(
+                                            box array,
+                                            box FieldElementExpression::Number(from + T::from(i)),
+                                        ),
+                                        FieldElementArrayExpression::Slice(..) => {
+                                            unreachable!("slice bounds should be numbers")
+                                        }
                                     },
                                     box FieldElementExpression::Number(T::from(0)),
                                 )
@@ -848,9 +1078,61 @@ impl<'ast> Flattener<'ast> {
                     }
                 }
             }
+            // booleans are already flattened to 0 or 1
+            FieldElementExpression::FromBoolean(box exp) => {
+                self.flatten_boolean_expression(functions_flattened, statements_flattened, exp)
+            }
         }
     }
 
+    /// Flattens a check that an index is in bounds
+    ///
+    /// # Arguments
+    ///
+    /// * `statements_flattened` - Vector where new flattened statements can be added.
+    /// * `index` - Index into the array, which is checked to be in `0..size`.
+    /// * `size` - Size of the array.
+    ///
+    /// # Remarks
+    ///
+    /// * We check that sum(if index == i then 1 else 0) == 1. Depending on the size,
+    /// * we could use a proper range check based on bits.
+    fn flatten_index_range_check<T: Field>(
+        &mut self,
+        functions_flattened: &Vec<FlatFunction<T>>,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        index: FieldElementExpression<'ast, T>,
+        size: usize,
+    ) {
+        let range_check = (0..size)
+            .map(|i| {
+                FieldElementExpression::IfElse(
+                    box BooleanExpression::Eq(
+                        box index.clone(),
+                        box FieldElementExpression::Number(T::from(i)),
+                    ),
+                    box FieldElementExpression::Number(T::from(1)),
+                    box FieldElementExpression::Number(T::from(0)),
+                )
+            })
+            .fold(FieldElementExpression::Number(T::from(0)), |acc, e| {
+                FieldElementExpression::Add(box acc, box e)
+            });
+
+        let range_check_statement = TypedStatement::Condition(
+            FieldElementExpression::Number(T::from(1)).into(),
+            range_check.into(),
+            None,
+            None,
+        );
+
+        self.flatten_statement(
+            functions_flattened,
+            statements_flattened,
+            range_check_statement,
+        );
+    }
+
     fn flatten_field_array_expression<T: Field>(
         &mut self,
         functions_flattened: &Vec<FlatFunction<T>>,
@@ -885,6 +1167,40 @@ impl<'ast> Flattener<'ast> {
                 assert!(exprs_flattened.expressions.len() == size); // outside of MultipleDefinition, FunctionCalls must return a single value
                 exprs_flattened.expressions
             }
+            FieldElementArrayExpression::Repeat(size, box e) => {
+                // flatten the element once and reuse it for every position
+                let e_flattened =
+                    self.flatten_field_expression(functions_flattened, statements_flattened, e);
+                vec![e_flattened; size]
+            }
+            FieldElementArrayExpression::Concat(box left, box right) => {
+                let mut flattened = self.flatten_field_array_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    left,
+                );
+                flattened.extend(self.flatten_field_array_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    right,
+                ));
+                flattened
+            }
+            FieldElementArrayExpression::Slice(_, box array, box from, box to) => {
+                let bound = |e: FieldElementExpression<'ast, T>| match e {
+                    FieldElementExpression::Number(n) => {
+                        n.to_dec_string().parse::<usize>().unwrap()
+                    }
+                    _ => unreachable!("slice bounds should be numbers"),
+                };
+                let (from, to) = (bound(from), bound(to));
+                let flattened = self.flatten_field_array_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    array,
+                );
+                flattened[from..to].to_vec()
+            }
             FieldElementArrayExpression::IfElse(
                 ref condition,
                 ref consequence,
@@ -924,7 +1240,7 @@ impl<'ast> Flattener<'ast> {
         stat: TypedStatement<'ast, T>,
     ) {
         match stat {
-            TypedStatement::Return(exprs) => {
+            TypedStatement::Return(exprs, _) => {
                 let flat_expressions = exprs
                     .into_iter()
                     .map(|expr| {
@@ -937,11 +1253,11 @@ impl<'ast> Flattener<'ast> {
                     expressions: flat_expressions,
                 }));
             }
-            TypedStatement::Declaration(_) => {
+            TypedStatement::Declaration(_, _) => {
                 // declarations have already been checked
                 ()
             }
-            TypedStatement::Definition(assignee, expr) => {
+            TypedStatement::Definition(assignee, expr, _) => {
                 // define n variables with n the number of primitive types for v_type
                 // assign them to the n primitive types for expr
 
@@ -1011,6 +1327,8 @@ impl<'ast> Flattener<'ast> {
                                         let range_check_statement = TypedStatement::Condition(
                                             FieldElementExpression::Number(T::from(1)).into(),
                                             range_check.into(),
+                                            None,
+                                            None,
                                         );
 
                                         self.flatten_statement(
@@ -1070,7 +1388,7 @@ impl<'ast> Flattener<'ast> {
                     }
                 }
             }
-            TypedStatement::Condition(expr1, expr2) => {
+            TypedStatement::Condition(expr1, expr2, _, _) => {
                 // flatten expr1 and expr2 to n flattened expressions with n the number of primitive types for expr1
                 // add n conditions to check equality of the n expressions
 
@@ -1156,7 +1474,35 @@ impl<'ast> Flattener<'ast> {
                     ),
                 }
             }
-            TypedStatement::For(var, start, end, statements) => {
+            TypedStatement::Assert(e, message, span) => match e {
+                // equalities are constrained directly rather than through the boolean they evaluate to
+                BooleanExpression::Eq(box e1, box e2) => self.flatten_statement(
+                    functions_flattened,
+                    statements_flattened,
+                    TypedStatement::Condition(e1.into(), e2.into(), message, span),
+                ),
+                BooleanExpression::BoolEq(box e1, box e2) => self.flatten_statement(
+                    functions_flattened,
+                    statements_flattened,
+                    TypedStatement::Condition(e1.into(), e2.into(), message, span),
+                ),
+                BooleanExpression::ArrayEq(box e1, box e2) => self.flatten_statement(
+                    functions_flattened,
+                    statements_flattened,
+                    TypedStatement::Condition(e1.into(), e2.into(), message, span),
+                ),
+                e => {
+                    let e = self.flatten_boolean_expression(
+                        functions_flattened,
+                        statements_flattened,
+                        e,
+                    );
+
+                    statements_flattened
+                        .push(FlatStatement::Condition(FlatExpression::Number(T::one()), e));
+                }
+            },
+            TypedStatement::For(var, start, end, statements, _) => {
                 let mut current = start;
                 while current < end {
                     statements_flattened.push(FlatStatement::Definition(
@@ -1169,7 +1515,7 @@ impl<'ast> Flattener<'ast> {
                     current = T::one() + &current;
                 }
             }
-            TypedStatement::MultipleDefinition(vars, rhs) => {
+            TypedStatement::MultipleDefinition(vars, rhs, _) => {
                 // flatten the right side to p = sum(var_i.type.primitive_count) expressions
                 // define p new variables to the right side expressions
 
@@ -1360,10 +1706,10 @@ mod tests {
             let function: TypedFunction<FieldPrime> = TypedFunction {
                 id: "main",
                 arguments: vec![Parameter::private(Variable::boolean("a".into()))],
-                statements: vec![TypedStatement::Return(vec![BooleanExpression::Identifier(
-                    "a".into(),
-                )
-                .into()])],
+                statements: vec![TypedStatement::Return(
+                    vec![BooleanExpression::Identifier("a".into()).into()],
+                    None,
+                )],
                 signature: Signature::new()
                     .inputs(vec![Type::Boolean])
                     .outputs(vec![Type::Boolean]),
@@ -1429,6 +1775,7 @@ mod tests {
                 vec![],
                 vec![Type::FieldElement, Type::FieldElement],
             ),
+            None,
         );
 
         flattener.flatten_statement(
@@ -1480,6 +1827,7 @@ mod tests {
                 )],
                 vec![Type::FieldElement, Type::FieldElement],
             ),
+            None,
         );
 
         let fun = TypedFunction {
@@ -1527,6 +1875,7 @@ mod tests {
                 "foo".to_string(),
                 vec![],
             )),
+            None,
         );
 
         flattener.flatten_statement(
@@ -1574,10 +1923,12 @@ mod tests {
                         box FieldElementExpression::Number(FieldPrime::from(1)),
                     )
                     .into(),
+                    None,
+                ),
+                TypedStatement::Return(
+                    vec![FieldElementExpression::Number(FieldPrime::from(1)).into()],
+                    None,
                 ),
-                TypedStatement::Return(vec![
-                    FieldElementExpression::Number(FieldPrime::from(1)).into()
-                ]),
             ],
         };
 
@@ -1614,8 +1965,12 @@ mod tests {
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element("a".into())),
                     FieldElementExpression::Number(FieldPrime::from(3)).into(),
+                    None,
+                ),
+                TypedStatement::Return(
+                    vec![FieldElementExpression::Identifier("a".into()).into()],
+                    None,
                 ),
-                TypedStatement::Return(vec![FieldElementExpression::Identifier("a".into()).into()]),
             ],
             signature: Signature {
                 inputs: vec![],
@@ -1626,9 +1981,10 @@ mod tests {
         let main = TypedFunction {
             id: "main",
             arguments: vec![],
-            statements: vec![TypedStatement::Return(vec![
-                FieldElementExpression::FunctionCall(String::from("foo"), vec![]).into(),
-            ])],
+            statements: vec![TypedStatement::Return(
+                vec![FieldElementExpression::FunctionCall(String::from("foo"), vec![]).into()],
+                None,
+            )],
             signature: Signature {
                 inputs: vec![],
                 outputs: vec![Type::FieldElement],
@@ -1680,6 +2036,7 @@ mod tests {
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element("a".into())),
                     FieldElementExpression::Number(FieldPrime::from(7)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element("b".into())),
@@ -1688,8 +2045,12 @@ mod tests {
                         box FieldElementExpression::Number(FieldPrime::from(4)),
                     )
                     .into(),
+                    None,
+                ),
+                TypedStatement::Return(
+                    vec![FieldElementExpression::Identifier("b".into()).into()],
+                    None,
                 ),
-                TypedStatement::Return(vec![FieldElementExpression::Identifier("b".into()).into()]),
             ],
             signature: Signature {
                 inputs: vec![],
@@ -1759,9 +2120,12 @@ mod tests {
             TypedFunction {
                 id: "foo",
                 arguments: vec![],
-                statements: vec![TypedStatement::Return(vec![TypedExpression::FieldElement(
-                    FieldElementExpression::Number(FieldPrime::from(1)),
-                )])],
+                statements: vec![TypedStatement::Return(
+                    vec![TypedExpression::FieldElement(
+                        FieldElementExpression::Number(FieldPrime::from(1)),
+                    )],
+                    None,
+                )],
                 signature: Signature::new()
                     .inputs(vec![])
                     .outputs(vec![Type::FieldElement]),
@@ -1769,14 +2133,17 @@ mod tests {
             TypedFunction {
                 id: "foo",
                 arguments: vec![],
-                statements: vec![TypedStatement::Return(vec![
-                    TypedExpression::FieldElement(FieldElementExpression::Number(
-                        FieldPrime::from(1),
-                    )),
-                    TypedExpression::FieldElement(FieldElementExpression::Number(
-                        FieldPrime::from(2),
-                    )),
-                ])],
+                statements: vec![TypedStatement::Return(
+                    vec![
+                        TypedExpression::FieldElement(FieldElementExpression::Number(
+                            FieldPrime::from(1),
+                        )),
+                        TypedExpression::FieldElement(FieldElementExpression::Number(
+                            FieldPrime::from(2),
+                        )),
+                    ],
+                    None,
+                )],
                 signature: Signature::new()
                     .inputs(vec![])
                     .outputs(vec![Type::FieldElement, Type::FieldElement]),
@@ -1791,6 +2158,7 @@ mod tests {
                             "foo".to_string(),
                             vec![],
                         )),
+                        None,
                     ),
                     TypedStatement::MultipleDefinition(
                         vec![
@@ -1802,10 +2170,14 @@ mod tests {
                             vec![],
                             vec![Type::FieldElement, Type::FieldElement],
                         ),
+                        None,
+                    ),
+                    TypedStatement::Return(
+                        vec![TypedExpression::FieldElement(
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                        )],
+                        None,
                     ),
-                    TypedStatement::Return(vec![TypedExpression::FieldElement(
-                        FieldElementExpression::Number(FieldPrime::from(1)),
-                    )]),
                 ],
                 signature: Signature::new()
                     .inputs(vec![])
@@ -1840,6 +2212,36 @@ mod tests {
         flattener.flatten_field_expression(&functions_flattened, &mut vec![], expression);
     }
 
+    #[test]
+    fn max() {
+        let a = || box FieldElementExpression::Number(FieldPrime::from(32));
+        let b = || box FieldElementExpression::Number(FieldPrime::from(4));
+
+        let flatten = |expression| {
+            let mut flattener = Flattener::new();
+            let mut functions_flattened = vec![];
+            flattener.load_corelib(&mut functions_flattened);
+
+            let mut statements_flattened = vec![];
+            let e = flattener.flatten_field_expression(
+                &functions_flattened,
+                &mut statements_flattened,
+                expression,
+            );
+            (statements_flattened, e)
+        };
+
+        // max(a, b) is flattened as `if a > b then a else b fi`
+        assert_eq!(
+            flatten(FieldElementExpression::Max(a(), b())),
+            flatten(FieldElementExpression::IfElse(
+                box BooleanExpression::Gt(a(), b()),
+                a(),
+                b()
+            ))
+        );
+    }
+
     #[test]
     fn geq_leq() {
         let mut flattener = Flattener::new();
@@ -1881,6 +2283,51 @@ mod tests {
         flattener.flatten_field_expression(&functions_flattened, &mut vec![], expression);
     }
 
+    #[test]
+    fn bool_eq() {
+        let expression = FieldElementExpression::IfElse(
+            box BooleanExpression::BoolEq(
+                box BooleanExpression::Lt(
+                    box FieldElementExpression::Number(FieldPrime::from(4)),
+                    box FieldElementExpression::Number(FieldPrime::from(20)),
+                ),
+                box BooleanExpression::Value(true),
+            ),
+            box FieldElementExpression::Number(FieldPrime::from(12)),
+            box FieldElementExpression::Number(FieldPrime::from(51)),
+        );
+
+        let mut flattener = Flattener::new();
+        let mut functions_flattened = vec![];
+        flattener.load_corelib(&mut functions_flattened);
+        flattener.flatten_field_expression(&functions_flattened, &mut vec![], expression);
+    }
+
+    #[test]
+    fn from_field() {
+        // bool(1) asserts that 1 == 1 * 1
+        let expression = BooleanExpression::FromField(box FieldElementExpression::Number(
+            FieldPrime::from(1),
+        ));
+
+        let mut flattener = Flattener::new();
+        let mut statements_flattened = vec![];
+        let flattened =
+            flattener.flatten_boolean_expression(&vec![], &mut statements_flattened, expression);
+
+        assert_eq!(flattened, FlatExpression::Number(FieldPrime::from(1)));
+        assert_eq!(
+            statements_flattened,
+            vec![FlatStatement::Condition(
+                FlatExpression::Number(FieldPrime::from(1)),
+                FlatExpression::Mult(
+                    box FlatExpression::Number(FieldPrime::from(1)),
+                    box FlatExpression::Number(FieldPrime::from(1)),
+                ),
+            )]
+        );
+    }
+
     #[test]
     fn div() {
         // a = 5 / b / b
@@ -1891,6 +2338,7 @@ mod tests {
         let definition = TypedStatement::Definition(
             TypedAssignee::Identifier(Variable::field_element("b".into())),
             FieldElementExpression::Number(FieldPrime::from(42)).into(),
+            None,
         );
 
         let statement = TypedStatement::Definition(
@@ -1903,6 +2351,7 @@ mod tests {
                 box FieldElementExpression::Identifier("b".into()),
             )
             .into(),
+            None,
         );
 
         flattener.flatten_statement(
@@ -2011,6 +2460,7 @@ mod tests {
                 ],
             )
             .into(),
+            None,
         );
         let expression = FieldElementArrayExpression::Identifier(3, "foo".into());
 
@@ -2054,6 +2504,7 @@ mod tests {
                 ],
             )
             .into(),
+            None,
         );
 
         flattener.flatten_statement(
@@ -2100,6 +2551,7 @@ mod tests {
                 ],
             )
             .into(),
+            None,
         );
 
         let expression = FieldElementExpression::Select(
@@ -2145,6 +2597,7 @@ mod tests {
                 ],
             )
             .into(),
+            None,
         );
 
         let sum = TypedStatement::Definition(
@@ -2166,6 +2619,7 @@ mod tests {
                 ),
             )
             .into(),
+            None,
         );
 
         flattener.flatten_statement::<FieldPrime>(
@@ -111,15 +111,24 @@ impl<T: Field> Executable<T> for Helper {
             Helper::Wasm(helper) => helper.execute(inputs),
         };
 
-        match result {
-            Ok(ref r) if r.len() != expected_output_count => Err(format!(
-                "invalid witness size: is {} but should be {}",
-                r.len(),
-                expected_output_count
-            )
-            .to_string()),
-            r => r,
-        }
+        check_output_count(result, expected_output_count)
+    }
+}
+
+/// Checks that a helper returned exactly one value per output, so that a misbehaving helper (for example an embedded
+/// gadget such as `Sha256Round`) fails when computing the witness rather than when proving
+fn check_output_count<T>(
+    result: Result<Vec<T>, String>,
+    expected_output_count: usize,
+) -> Result<Vec<T>, String> {
+    match result {
+        Ok(ref r) if r.len() != expected_output_count => Err(format!(
+            "invalid witness size: is {} but should be {}",
+            r.len(),
+            expected_output_count
+        )
+        .to_string()),
+        r => r,
     }
 }
 
@@ -168,4 +177,32 @@ mod tests {
             assert_eq!(r, &res[..]);
         }
     }
+
+    mod output_count {
+        use super::*;
+
+        #[test]
+        fn wrong_count() {
+            // a gadget returning fewer values than its outputs
+            let (_, expected) = RustHelper::Sha256Round.get_signature();
+            let result: Result<Vec<FieldPrime>, String> = Ok(vec![FieldPrime::from(1); 42]);
+
+            assert_eq!(
+                check_output_count(result, expected),
+                Err(format!(
+                    "invalid witness size: is 42 but should be {}",
+                    expected
+                ))
+            );
+        }
+
+        #[test]
+        fn right_count() {
+            let result: Result<Vec<FieldPrime>, String> = Ok(vec![FieldPrime::from(1); 2]);
+            assert_eq!(
+                check_output_count(result, 2),
+                Ok(vec![FieldPrime::from(1); 2])
+            );
+        }
+    }
 }
@@ -6,7 +6,7 @@
 
 use crate::absy::*;
 use crate::compile::compile_aux;
-use crate::compile::{CompileErrorInner, CompileErrors};
+use crate::compile::{CompileErrorInner, CompileErrors, CompileWarning};
 use crate::flat_absy::*;
 use crate::parser::Position;
 use std::fmt;
@@ -132,18 +132,19 @@ impl Importer {
         Importer {}
     }
 
-    // Inject dependencies declared for `destination`
+    // Inject dependencies declared for `destination`, returning the warnings found while compiling them
     // The lifetime of the Program before injection outlives the lifetime after
     pub fn apply_imports<'before, 'after, T: Field, S: BufRead, E: Into<Error>>(
         &self,
         destination: Prog<'before, T>,
         location: Option<String>,
         resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
-    ) -> Result<Prog<'after, T>, CompileErrors>
+    ) -> Result<(Prog<'after, T>, Vec<CompileWarning>), CompileErrors>
     where
         'before: 'after,
     {
         let mut origins: Vec<CompiledImport<T>> = vec![];
+        let mut warnings = vec![];
 
         for import in destination.imports.iter() {
             let pos = import.pos();
@@ -200,8 +201,10 @@ impl Importer {
                 match resolve_option {
                     Some(resolve) => match resolve(&location, &import.source) {
                         Ok((mut reader, location, auto_alias)) => {
-                            let compiled = compile_aux(&mut reader, Some(location), resolve_option)
-                                .map_err(|e| e.with_context(Some(import.source.clone())))?;
+                            let (compiled, import_warnings) =
+                                compile_aux(&mut reader, Some(location), resolve_option)
+                                    .map_err(|e| e.with_context(Some(import.source.clone())))?;
+                            warnings.extend(import_warnings);
                             let alias = match import.alias {
                                 Some(ref alias) => alias.clone(),
                                 None => auto_alias,
@@ -227,11 +230,14 @@ impl Importer {
             }
         }
 
-        Ok(Prog {
-            imports: vec![],
-            functions: destination.clone().functions,
-            imported_functions: origins.into_iter().map(|o| o.flat_func).collect(),
-        })
+        Ok((
+            Prog {
+                imports: vec![],
+                functions: destination.clone().functions,
+                imported_functions: origins.into_iter().map(|o| o.flat_func).collect(),
+            },
+            warnings,
+        ))
     }
 }
 
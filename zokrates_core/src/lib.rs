@@ -10,15 +10,19 @@ extern crate serde_derive;
 extern crate bellman;
 extern crate bincode;
 extern crate ff;
+extern crate fnv;
 extern crate lazy_static;
 extern crate pairing;
 #[cfg(feature = "wasm")]
 extern crate parity_wasm;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate regex;
 #[cfg(feature = "wasm")]
 extern crate rustc_hex;
 #[cfg(feature = "wasm")]
 extern crate serde_bytes;
+extern crate sha2;
 #[cfg(feature = "wasm")]
 extern crate wasmi;
 extern crate zokrates_embed;
@@ -33,8 +37,6 @@ mod optimizer;
 mod parser;
 mod semantics;
 mod standard;
-mod static_analysis;
-mod typed_absy;
 mod types;
 
 pub mod absy;
@@ -42,3 +44,5 @@ pub mod compile;
 pub mod flat_absy;
 pub mod ir;
 pub mod proof_system;
+pub mod static_analysis;
+pub mod typed_absy;
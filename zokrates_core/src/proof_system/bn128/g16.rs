@@ -1,7 +1,9 @@
 use crate::ir;
 use crate::proof_system::bn128::utils::bellman::Computation;
-use crate::proof_system::bn128::utils::solidity::{SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB};
-use crate::proof_system::ProofSystem;
+use crate::proof_system::bn128::utils::solidity::{
+    next_vk_value, SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB,
+};
+use crate::proof_system::{ProofSystem, VerifierExportError};
 use bellman::groth16::Parameters;
 use regex::Regex;
 use std::fs::File;
@@ -55,7 +57,10 @@ impl ProofSystem for G16 {
         true
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String {
+    fn export_solidity_verifier(
+        &self,
+        reader: BufReader<File>,
+    ) -> Result<String, VerifierExportError> {
         let mut lines = reader.lines();
 
         let mut template_text = String::from(CONTRACT_TEMPLATE);
@@ -70,24 +75,15 @@ impl ProofSystem for G16 {
         let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
 
         for _ in 0..4 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
+            let value = next_vk_value(&mut lines)?;
             template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
+                .replace(template_text.as_str(), value.as_str())
                 .into_owned();
         }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let gamma_abc_count: i32 = current_line_split[1].trim().parse().unwrap();
+        let gamma_abc_count: i32 = next_vk_value(&mut lines)?.parse().map_err(|_| {
+            VerifierExportError::MalformedKey(String::from("invalid gammaABC length"))
+        })?;
 
         template_text = vk_gamma_abc_len_regex
             .replace(
@@ -105,17 +101,12 @@ impl ProofSystem for G16 {
         let mut gamma_abc_repeat_text = String::new();
         for x in 0..gamma_abc_count {
             let mut curr_template = gamma_abc_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
+            let value = next_vk_value(&mut lines)?;
             curr_template = vk_gamma_abc_index_regex
                 .replace(curr_template.as_str(), format!("{}", x).as_str())
                 .into_owned();
             curr_template = vk_gamma_abc_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
+                .replace(curr_template.as_str(), value.as_str())
                 .into_owned();
             gamma_abc_repeat_text.push_str(curr_template.as_str());
             if x < gamma_abc_count - 1 {
@@ -129,10 +120,10 @@ impl ProofSystem for G16 {
         let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
         template_text = re.replace_all(&template_text, "uint256($v)").to_string();
 
-        format!(
+        Ok(format!(
             "{}{}{}",
             SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
-        )
+        ))
     }
 }
 
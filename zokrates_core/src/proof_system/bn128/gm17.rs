@@ -3,8 +3,10 @@ extern crate libc;
 use self::libc::{c_char, c_int};
 use ir;
 use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup};
-use proof_system::bn128::utils::solidity::{SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB};
-use proof_system::ProofSystem;
+use proof_system::bn128::utils::solidity::{
+    next_vk_value, SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB,
+};
+use proof_system::{ProofSystem, VerifierExportError};
 use regex::Regex;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -105,7 +107,10 @@ impl ProofSystem for GM17 {
         }
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String {
+    fn export_solidity_verifier(
+        &self,
+        reader: BufReader<File>,
+    ) -> Result<String, VerifierExportError> {
         let mut lines = reader.lines();
 
         let mut template_text = String::from(CONTRACT_TEMPLATE);
@@ -120,24 +125,15 @@ impl ProofSystem for GM17 {
         let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
 
         for _ in 0..5 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
+            let value = next_vk_value(&mut lines)?;
             template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
+                .replace(template_text.as_str(), value.as_str())
                 .into_owned();
         }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let query_count: i32 = current_line_split[1].trim().parse().unwrap();
+        let query_count: i32 = next_vk_value(&mut lines)?.parse().map_err(|_| {
+            VerifierExportError::MalformedKey(String::from("invalid query length"))
+        })?;
 
         template_text = vk_query_len_regex
             .replace(template_text.as_str(), format!("{}", query_count).as_str())
@@ -152,17 +148,12 @@ impl ProofSystem for GM17 {
         let mut query_repeat_text = String::new();
         for x in 0..query_count {
             let mut curr_template = query_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
+            let value = next_vk_value(&mut lines)?;
             curr_template = vk_query_index_regex
                 .replace(curr_template.as_str(), format!("{}", x).as_str())
                 .into_owned();
             curr_template = vk_query_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
+                .replace(curr_template.as_str(), value.as_str())
                 .into_owned();
             query_repeat_text.push_str(curr_template.as_str());
             if x < query_count - 1 {
@@ -176,10 +167,10 @@ impl ProofSystem for GM17 {
         let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
         template_text = re.replace_all(&template_text, "uint256($v)").to_string();
 
-        format!(
+        Ok(format!(
             "{}{}{}",
             SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
-        )
+        ))
     }
 }
 
@@ -1,3 +1,23 @@
+use crate::proof_system::VerifierExportError;
+use std::io::{BufRead, Lines};
+
+/// Returns the value of the next `key = value` line of a verification key
+pub fn next_vk_value<B: BufRead>(lines: &mut Lines<B>) -> Result<String, VerifierExportError> {
+    let line = lines
+        .next()
+        .ok_or_else(|| VerifierExportError::MalformedKey(String::from("unexpected end of file")))?
+        .map_err(|e| VerifierExportError::MalformedKey(e.to_string()))?;
+
+    let split: Vec<&str> = line.split("=").collect();
+    match split.len() {
+        2 => Ok(split[1].trim().to_string()),
+        _ => Err(VerifierExportError::MalformedKey(format!(
+            "expected `key = value`, found `{}`",
+            line
+        ))),
+    }
+}
+
 pub const SOLIDITY_G2_ADDITION_LIB: &str = r#"// This file is LGPL3 Licensed
 
 /**
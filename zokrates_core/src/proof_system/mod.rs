@@ -1,6 +1,7 @@
 mod bn128;
 pub mod zkinterface;
 
+use std::fmt;
 use std::fs::File;
 use zokrates_field::field::FieldPrime;
 
@@ -14,9 +15,138 @@ pub use self::zkinterface::ZkInterface;
 use crate::ir;
 use std::io::BufReader;
 
+/// A sparse linear combination of variables, as pairs of variable index and coefficient.
+/// The variable of index `0` is the constant one wire, so that a constant `k` is the term `(0, k)`.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct LinearCombination(Vec<(usize, FieldPrime)>);
+
+impl LinearCombination {
+    pub fn new() -> LinearCombination {
+        LinearCombination(vec![])
+    }
+
+    /// Returns the constant linear combination `value`, a multiple of the one wire
+    pub fn constant(value: FieldPrime) -> LinearCombination {
+        LinearCombination::new().add_term(self::zkinterface::ONE_WIRE_ID as usize, value)
+    }
+
+    /// Adds `coeff` times the variable of index `var` to this linear combination
+    pub fn add_term(mut self, var: usize, coeff: FieldPrime) -> LinearCombination {
+        self.0.push((var, coeff));
+        self
+    }
+
+    /// Returns the terms of this linear combination, in insertion order
+    pub fn terms(&self) -> &[(usize, FieldPrime)] {
+        &self.0
+    }
+}
+
+impl From<Vec<(usize, FieldPrime)>> for LinearCombination {
+    fn from(terms: Vec<(usize, FieldPrime)>) -> LinearCombination {
+        LinearCombination(terms)
+    }
+}
+
+/// The size of the constraint system generated by a setup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupResult {
+    /// Number of constraints of the system
+    pub num_constraints: usize,
+    /// Number of variables of the system, counting the one wire
+    pub num_variables: usize,
+    /// Id of the first output of the program, which follows its public inputs
+    pub first_output_id: u64,
+    /// Hash of the constraint system, to check that a proving key matches a circuit
+    pub constraint_system_hash: Vec<u8>,
+}
+
+/// Error returned by `ProofSystem::export_solidity_verifier`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifierExportError {
+    /// The backend cannot export a Solidity verifier
+    Unsupported,
+    /// The verification key could not be read
+    MalformedKey(String),
+}
+
+impl fmt::Display for VerifierExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerifierExportError::Unsupported => {
+                write!(f, "this proving scheme does not support exporting a verifier")
+            }
+            VerifierExportError::MalformedKey(ref reason) => {
+                write!(f, "malformed verification key: {}", reason)
+            }
+        }
+    }
+}
+
+/// Error returned by `ProofSystem::verify`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The backend cannot verify proofs locally
+    Unsupported,
+    /// The verification key or the proof could not be read
+    MalformedArtifact(String),
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerificationError::Unsupported => {
+                write!(f, "this proving scheme does not support verifying proofs locally")
+            }
+            VerificationError::MalformedArtifact(ref reason) => {
+                write!(f, "malformed verification key or proof: {}", reason)
+            }
+        }
+    }
+}
+
+/// A source of rank-1 constraints `a * b = c`, which backends can consume one constraint at a time.
+/// Any iterator over `(a, b, c)` triples is a source, for example `Vec::into_iter` on a vector of constraints.
+pub trait ConstraintSource:
+    Iterator<Item = (LinearCombination, LinearCombination, LinearCombination)>
+{
+}
+
+impl<I> ConstraintSource for I where
+    I: Iterator<Item = (LinearCombination, LinearCombination, LinearCombination)>
+{
+}
+
 pub trait ProofSystem {
     fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str);
 
+    /// Runs `setup`, returning the size of the generated constraint system.
+    /// Returns `None` if this backend does not report it.
+    fn setup_with_result(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+    ) -> Option<SetupResult> {
+        self.setup(program, pk_path, vk_path);
+        None
+    }
+
+    /// Runs the setup from a source of constraints rather than from a program, so that the whole constraint system
+    /// does not need to be held in memory. The variables `0..public_count` are public, the variables
+    /// `public_count..variable_count` are private.
+    /// Returns `false` if this backend needs the program to run the setup.
+    fn setup_with_constraints(
+        &self,
+        _constraints: &mut dyn ConstraintSource,
+        _public_count: usize,
+        _variable_count: usize,
+        _pk_path: &str,
+        _vk_path: &str,
+    ) -> bool {
+        false
+    }
+
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
@@ -25,5 +155,21 @@ pub trait ProofSystem {
         proof_path: &str,
     ) -> bool;
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String;
+    /// Returns a Solidity contract verifying proofs against the verification key read from `reader`
+    fn export_solidity_verifier(
+        &self,
+        reader: BufReader<File>,
+    ) -> Result<String, VerifierExportError>;
+
+    /// Verifies the proof at `proof_path` against the verification key at `vk_path`, for the public inputs and
+    /// outputs `public_inputs`.
+    /// Returns an error if this backend cannot verify proofs locally or if the files cannot be read.
+    fn verify(
+        &self,
+        _vk_path: &str,
+        _proof_path: &str,
+        _public_inputs: &[FieldPrime],
+    ) -> Result<bool, VerificationError> {
+        Err(VerificationError::Unsupported)
+    }
 }
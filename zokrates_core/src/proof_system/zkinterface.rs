@@ -1,15 +1,24 @@
 use flat_absy::flat_variable::FlatVariable;
+use flat_absy::FlatProg;
 use ir::{self, Statement};
-use proof_system::ProofSystem;
-use std::collections::HashMap;
-use std::fs::File;
+use optimizer::Optimize;
+use proof_system::{
+    ConstraintSource, LinearCombination, ProofSystem, SetupResult, VerificationError,
+    VerifierExportError,
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
 use std::io::{BufReader, Write};
 use zkinterface::{
     flatbuffers::{FlatBufferBuilder, WIPOffset},
+    reading::{Messages, Term},
     writing::{CircuitOwned, VariablesOwned},
     zkinterface_generated::zkinterface::{
         BilinearConstraint,
         BilinearConstraintArgs,
+        KeyValue,
+        KeyValueArgs,
         Message,
         R1CSConstraints,
         R1CSConstraintsArgs,
@@ -25,18 +34,136 @@ use zokrates_field::field::{Field, FieldPrime};
 
 pub static FIELD_LENGTH: usize = 32;
 
+/// Id of the variable which is always 1, the constant terms of linear combinations are multiples of it
+pub const ONE_WIRE_ID: u64 = 0;
+/// Id of the first public input. The public inputs follow the one wire and are followed by the outputs.
+pub const FIRST_INPUT_ID: u64 = ONE_WIRE_ID + 1;
+
+/// Returns the id of the first output of a circuit with `input_count` public inputs
+pub fn first_output_id(input_count: usize) -> u64 {
+    FIRST_INPUT_ID + input_count as u64
+}
+
+/// The variable ids of a zkinterface circuit, as a caller needs them to assemble an assignment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceDescription {
+    /// Ids of the public variables in order, starting with the one wire
+    pub public_input_ids: Vec<u64>,
+    /// Id of the first private variable, which follow the public ones
+    pub first_local_id: u64,
+    /// Id of the first variable not used by the circuit
+    pub free_variable_id: u64,
+}
+
 pub struct ZkInterface {}
 
 impl ZkInterface {
     pub fn new() -> ZkInterface {
         ZkInterface {}
     }
+
+    /// Describes the ids of a circuit over `variables` variables, the first `num_public_inputs` of which are public,
+    /// counting the one wire
+    pub fn describe_interface(variables: usize, num_public_inputs: usize) -> InterfaceDescription {
+        assert!(num_public_inputs <= variables);
+
+        let first_local_id = ONE_WIRE_ID + num_public_inputs as u64;
+
+        InterfaceDescription {
+            public_input_ids: (ONE_WIRE_ID..first_local_id).collect(),
+            first_local_id,
+            free_variable_id: ONE_WIRE_ID + variables as u64,
+        }
+    }
+
+    /// Maps each of `variables`, in the order of the R1CS as returned by `r1cs_variables`, to its zkinterface id
+    pub fn variable_id_map(variables: &[FlatVariable]) -> HashMap<FlatVariable, u64> {
+        variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v, ONE_WIRE_ID + i as u64))
+            .collect()
+    }
+
+    /// Checks that `assignment`, the values of the variables by id, satisfies the constraints `a_i * b_i == c_i`.
+    /// Returns the index of the first unsatisfied constraint otherwise.
+    pub fn check_witness(
+        a: &[LinearCombination],
+        b: &[LinearCombination],
+        c: &[LinearCombination],
+        assignment: &[FieldPrime],
+    ) -> Result<(), usize> {
+        // a term on a variable missing from the assignment cannot be satisfied
+        let evaluate = |lc: &LinearCombination| {
+            lc.terms().iter().try_fold(FieldPrime::from(0), |acc, (id, coeff)| {
+                assignment.get(*id).map(|v| acc + coeff.clone() * v.clone())
+            })
+        };
+
+        for i in 0..a.len() {
+            let satisfied = match (evaluate(&a[i]), evaluate(&b[i]), evaluate(&c[i])) {
+                (Some(a), Some(b), Some(c)) => a * b == c,
+                _ => false,
+            };
+
+            if !satisfied {
+                return Err(i);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the messages of `setup` and `generate_proof` for `program` to a single stream: the `Circuit` header, the
+    /// `R1CSConstraints`, then the `Circuit` with the public values and the `Witness`. Returns `false` without writing
+    /// anything if `witness` does not assign every variable of the constraint system, like `generate_proof`.
+    pub fn write_combined_stream<W: Write>(
+        mut writer: W,
+        program: ir::Prog<FieldPrime>,
+        witness: ir::Witness<FieldPrime>,
+    ) -> bool {
+        let (public_inputs, private_inputs) = match prepare_generate_proof(program.clone(), witness) {
+            Ok(values) => values,
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        };
+
+        setup(program, &mut writer);
+        generate_proof_with_values(&public_inputs, &private_inputs, &mut writer)
+    }
 }
 
 impl ProofSystem for ZkInterface {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, _vk_path: &str) {
+    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+        self.setup_with_result(program, pk_path, vk_path);
+    }
+
+    fn setup_with_result(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+    ) -> Option<SetupResult> {
         let mut out_file = File::create(pk_path).unwrap();
-        setup(program, &mut out_file)
+        let result = setup_with_result(program, &mut out_file);
+        copy_constraint_system(pk_path, vk_path);
+        Some(result)
+    }
+
+    fn setup_with_constraints(
+        &self,
+        constraints: &mut dyn ConstraintSource,
+        public_count: usize,
+        variable_count: usize,
+        pk_path: &str,
+        vk_path: &str,
+    ) -> bool {
+        let mut out_file = File::create(pk_path).unwrap();
+        setup_from_source(constraints, public_count, variable_count, None, &mut out_file);
+        copy_constraint_system(pk_path, vk_path);
+        true
     }
 
     fn generate_proof(
@@ -50,86 +177,454 @@ impl ProofSystem for ZkInterface {
         generate_proof(program, witness, &mut out_file)
     }
 
-    fn export_solidity_verifier(&self, _reader: BufReader<File>) -> String {
-        "export_solidity_verifier is not implemented".to_string()
+    fn export_solidity_verifier(
+        &self,
+        _reader: BufReader<File>,
+    ) -> Result<String, VerifierExportError> {
+        Err(VerifierExportError::Unsupported)
     }
+
+    // a zkinterface proof is the assignment itself, which is checked against the constraint system `setup` wrote to
+    // `vk_path`
+    fn verify(
+        &self,
+        vk_path: &str,
+        proof_path: &str,
+        public_inputs: &[FieldPrime],
+    ) -> Result<bool, VerificationError> {
+        let mut messages = Messages::new(0);
+        for path in &[vk_path, proof_path] {
+            messages
+                .read_file(path)
+                .map_err(|e| VerificationError::MalformedArtifact(format!("{}: {}", path, e)))?;
+        }
+        Ok(verify(&messages, public_inputs))
+    }
+}
+
+/// Write the messages of `setup` for `program`, returning the hash of its constraint system
+pub fn setup<W: Write>(program: ir::Prog<FieldPrime>, out_file: &mut W) -> Vec<u8> {
+    setup_program(program, false, out_file).constraint_system_hash
+}
+
+/// Write the messages of `setup` for `program`, returning the size of its constraint system along with its hash
+pub fn setup_with_result<W: Write>(program: ir::Prog<FieldPrime>, out_file: &mut W) -> SetupResult {
+    setup_program(program, false, out_file)
+}
+
+/// Write the messages of `setup` for a flattened program, returning the hash of its constraint system. The program is
+/// converted and optimized as in `compile` first.
+pub fn from_flat_prog<W: Write>(program: FlatProg<FieldPrime>, out_file: &mut W) -> Vec<u8> {
+    setup(ir::Prog::from(program).optimize(), out_file)
+}
+
+/// Write the messages of `setup` like `setup`, labelling each constraint with the statement of `program` it comes from
+pub fn setup_with_labels<W: Write>(program: ir::Prog<FieldPrime>, out_file: &mut W) -> Vec<u8> {
+    setup_program(program, true, out_file).constraint_system_hash
 }
 
-pub fn setup<W: Write>(program: ir::Prog<FieldPrime>, out_file: &mut W) {
+fn setup_program<W: Write>(
+    program: ir::Prog<FieldPrime>,
+    labelled: bool,
+    out_file: &mut W,
+) -> SetupResult {
+    let labels = match labelled {
+        true => Some(constraint_labels(&program)),
+        false => None,
+    };
+
+    let input_count = program.private.iter().filter(|private| !**private).count();
+
     // transform to R1CS
     let (variables, first_local_id, a, b, c) = r1cs_program(program);
-    let free_variable_id = variables.len() as u64;
 
-    // Write Return message including free_variable_id.
-    write_circuit(
-        first_local_id as u64,
+    let num_constraints = a.len();
+    let num_variables = variables.len();
+
+    let mut constraints = a
+        .into_iter()
+        .zip(b.into_iter())
+        .zip(c.into_iter())
+        .map(|((a, b), c)| (a.into(), b.into(), c.into()));
+
+    let constraint_system_hash = setup_from_source(
+        &mut constraints,
+        first_local_id,
+        num_variables,
+        labels.as_ref().map(|l| l.as_slice()),
+        out_file,
+    );
+
+    SetupResult {
+        num_constraints,
+        num_variables,
+        first_output_id: first_output_id(input_count),
+        constraint_system_hash,
+    }
+}
+
+/// Returns a label for each constraint of `program`, in the order of the R1CS: the constraint statement itself,
+/// which names the variables of the flattened program
+pub fn constraint_labels<T: Field>(program: &ir::Prog<T>) -> Vec<String> {
+    program
+        .main
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Constraint(..) => Some(s.to_string()),
+            Statement::Directive(..) => None,
+        })
+        .collect()
+}
+
+/// Returns the variables of `program` in the order of the R1CS `setup` writes, the one wire first
+pub fn r1cs_variables<T: Field>(program: ir::Prog<T>) -> Vec<FlatVariable> {
+    r1cs_program(program).0
+}
+
+/// Write the messages of `setup` from a source of constraints over `variable_count` variables, the first
+/// `public_count` of which are public, returning the hash of the constraint system. If `labels` are given, the i-th
+/// label is attached to the i-th constraint.
+pub fn setup_from_source<W: Write>(
+    constraints: &mut dyn ConstraintSource,
+    public_count: usize,
+    variable_count: usize,
+    labels: Option<&[String]>,
+    out_file: &mut W,
+) -> Vec<u8> {
+    let writer = Writer::new();
+    let interface = ZkInterface::describe_interface(variable_count, public_count);
+
+    // Write Circuit message including free_variable_id.
+    write_circuit_header(
+        interface.free_variable_id,
+        &interface.public_input_ids,
+        &FieldPrime::max_value(),
+        out_file,
+    );
+
+    // Write R1CSConstraints message, hashing the constraints as they go by.
+    let mut hasher = Sha256::new();
+    {
+        let mut constraints = constraints.inspect(|c| hash_constraint(&mut hasher, c));
+        out_file.write_all(&writer.r1cs_from_source(&mut constraints, labels)).unwrap();
+    }
+    hasher.result().to_vec()
+}
+
+/// SHA-256 hash of a constraint system, computed on its canonical form so that it does not depend on the order of
+/// the terms of its linear combinations. Use it to check that a proving key was generated for a given circuit.
+pub fn constraint_system_hash(constraints: &mut dyn ConstraintSource) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+
+    for c in constraints {
+        hash_constraint(&mut hasher, &c);
+    }
+
+    hasher.result().to_vec()
+}
+
+fn hash_constraint(
+    hasher: &mut Sha256,
+    constraint: &(LinearCombination, LinearCombination, LinearCombination),
+) {
+    for lc in &[&constraint.0, &constraint.1, &constraint.2] {
+        let terms = canonical_linear_combination(lc);
+
+        // prefix each linear combination with its length so that terms cannot move from one to the next
+        hasher.input(&(terms.len() as u64).to_le_bytes());
+        for (id, value) in terms {
+            hasher.input(&(id as u64).to_le_bytes());
+            hasher.input(&encode_field_element(&value));
+        }
+    }
+}
+
+// zkinterface has no verification key, proofs are verified against the constraint system itself. Provers read it
+// from the proving key file, so the verification key file is a copy of it
+fn copy_constraint_system(pk_path: &str, vk_path: &str) {
+    if pk_path != vk_path {
+        fs::copy(pk_path, vk_path).unwrap();
+    }
+}
+
+/// Write a `Circuit` message declaring the instance variables of a constraint system, its free variable id and its
+/// field, given by its largest element `field_maximum`, so that zkinterface tools can consume it
+pub fn write_circuit_header<W: Write>(
+    free_variable_id: u64,
+    instance_variables: &[u64],
+    field_maximum: &FieldPrime,
+    out_file: &mut W,
+) {
+    let header = CircuitOwned {
+        connections: VariablesOwned {
+            variable_ids: instance_variables.to_vec(),
+            values: None,
+        },
         free_variable_id,
-        None,
-        true,
-        out_file);
+        r1cs_generation: true,
+        field_maximum: Some(encode_field_element(field_maximum)),
+    };
 
-    // Write R1CSConstraints message.
-    write_r1cs(&a, &b, &c, out_file);
+    header.write(out_file).unwrap();
 }
 
+/// Write the messages of `generate_proof` for `program`. Returns `false` without writing anything if `witness` does not
+/// assign every variable of the constraint system, as the assignment would not match the circuit written by `setup`.
 pub fn generate_proof<W: Write>(
     program: ir::Prog<FieldPrime>,
     witness: ir::Witness<FieldPrime>,
     out_file: &mut W,
 ) -> bool {
-    let (
-        public_inputs_arr,
-        private_inputs_arr,
-    ) = prepare_generate_proof(program, witness);
+    let (public_inputs_arr, private_inputs_arr) = match prepare_generate_proof(program, witness) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+
+    generate_proof_with_values(&public_inputs_arr, &private_inputs_arr, out_file)
+}
+
+/// Checks that the messages of `generate_proof` in `messages` satisfy the constraint system of the messages of `setup`,
+/// and that the public variables after the one wire are `public_inputs`
+pub fn verify(messages: &Messages, public_inputs: &[FieldPrime]) -> bool {
+    let decode = |value: &[u8]| FieldPrime::from_byte_vector(value.to_vec());
+
+    let (public, private) = match (messages.connection_variables(), messages.private_variables()) {
+        (Some(public), Some(private)) => (public, private),
+        _ => return false,
+    };
+
+    let expected: Vec<_> = std::iter::once(FieldPrime::from(1))
+        .chain(public_inputs.iter().cloned())
+        .collect();
+    if public.iter().map(|v| decode(v.value)).collect::<Vec<_>>() != expected {
+        return false;
+    }
+
+    let assignment: HashMap<u64, FieldPrime> = public
+        .iter()
+        .chain(private.iter())
+        .map(|v| (v.id, decode(v.value)))
+        .collect();
+
+    // a term on a variable missing from the assignment cannot be satisfied
+    let evaluate = |terms: &[Term]| {
+        terms.iter().try_fold(FieldPrime::from(0), |acc, t| {
+            assignment.get(&t.id).map(|v| acc + decode(t.value) * v.clone())
+        })
+    };
+
+    messages.iter_constraints().all(|c| {
+        match (evaluate(&c.a), evaluate(&c.b), evaluate(&c.c)) {
+            (Some(a), Some(b), Some(c)) => a * b == c,
+            _ => false,
+        }
+    })
+}
+
+/// Write the messages of `generate_proof` from the values of the public and private variables, in the order of the R1CS
+pub fn generate_proof_with_values<W: Write>(
+    public_inputs: &[FieldPrime],
+    local_values: &[FieldPrime],
+    out_file: &mut W,
+) -> bool {
+    let interface = ZkInterface::describe_interface(
+        public_inputs.len() + local_values.len(),
+        public_inputs.len(),
+    );
 
-    let first_local_id = public_inputs_arr.len() as u64;
-    let free_variable_id = first_local_id + private_inputs_arr.len() as u64;
+    let writer = Writer::new();
 
     // Write Return message including output values.
-    write_circuit(
-        first_local_id,
-        free_variable_id,
-        Some(&public_inputs_arr),
-        false,
-        out_file);
+    out_file
+        .write_all(&writer.circuit_return(
+            interface.first_local_id,
+            interface.free_variable_id,
+            Some(public_inputs),
+            false,
+        ))
+        .unwrap();
 
     // Write assignment to local variables.
-    write_assignment(
-        first_local_id as u64,
-        &private_inputs_arr,
-        out_file);
+    out_file
+        .write_all(&writer.assignment(interface.first_local_id, local_values))
+        .unwrap();
 
     true
 }
 
 
-fn write_r1cs<W: Write>(
-    a: &Vec<Vec<(usize, FieldPrime)>>,
-    b: &Vec<Vec<(usize, FieldPrime)>>,
-    c: &Vec<Vec<(usize, FieldPrime)>>,
-    out_file: &mut W,
-) {
-    let mut builder = FlatBufferBuilder::new();
+/// Builder for zkinterface messages, independent of the `ProofSystem` trait.
+/// Each method returns a size-prefixed flatbuffer message ready to be written to a stream.
+pub struct Writer {}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer {}
+    }
+
+    /// Build an `R1CSConstraints` message from the sparse rows of the `a`, `b` and `c` matrices
+    pub fn r1cs(
+        &self,
+        a: &[LinearCombination],
+        b: &[LinearCombination],
+        c: &[LinearCombination],
+    ) -> Vec<u8> {
+        self.r1cs_with_labels(a, b, c, None)
+    }
+
+    /// Build an `R1CSConstraints` message like `r1cs`, attaching the i-th label to the i-th constraint
+    pub fn r1cs_with_labels(
+        &self,
+        a: &[LinearCombination],
+        b: &[LinearCombination],
+        c: &[LinearCombination],
+        labels: Option<&[String]>,
+    ) -> Vec<u8> {
+        let mut builder = FlatBufferBuilder::new();
+
+        // create vector of
+        let mut vector_lc = vec![];
+
+        for i in 0..a.len() {
+            vector_lc.push(convert_constraint(&mut builder, &a[i], &b[i], &c[i]));
+        }
+
+        finish_r1cs(builder, vector_lc, labels)
+    }
+
+    /// Build an `R1CSConstraints` message from a source of constraints, consuming one constraint at a time
+    pub fn r1cs_from_source(
+        &self,
+        constraints: &mut dyn ConstraintSource,
+        labels: Option<&[String]>,
+    ) -> Vec<u8> {
+        let mut builder = FlatBufferBuilder::new();
+
+        let mut vector_lc = vec![];
+
+        for (a, b, c) in constraints {
+            vector_lc.push(convert_constraint(&mut builder, &a, &b, &c));
+        }
+
+        finish_r1cs(builder, vector_lc, labels)
+    }
 
-    // create vector of
-    let mut vector_lc = vec![];
+    /// Build a `Witness` message assigning `local_values` to the variables starting at `first_local_id`
+    pub fn assignment(
+        &self,
+        first_local_id: u64,
+        local_values: &[FieldPrime],
+    ) -> Vec<u8> {
+        let mut builder = &mut FlatBufferBuilder::new();
+
+        let mut ids = vec![];
+        let mut values = vec![];
 
-    for i in 0..a.len() {
-        let a_var_val = convert_linear_combination(&mut builder, &a[i]);
-        let b_var_val = convert_linear_combination(&mut builder, &b[i]);
-        let c_var_val = convert_linear_combination(&mut builder, &c[i]);
+        for i in 0..local_values.len() {
+            ids.push(first_local_id + i as u64);
 
-        let lc = BilinearConstraint::create(&mut builder, &BilinearConstraintArgs {
-            linear_combination_a: Some(a_var_val),
-            linear_combination_b: Some(b_var_val),
-            linear_combination_c: Some(c_var_val),
+            values.append(&mut encode_field_element(&local_values[i]));
+        }
+
+        let ids = builder.create_vector(&ids);
+        let values = builder.create_vector(&values);
+        let values = Variables::create(&mut builder, &VariablesArgs {
+            variable_ids: Some(ids),
+            values: Some(values),
+            info: None,
+        });
+        let assign = Witness::create(&mut builder, &WitnessArgs {
+            assigned_variables: Some(values),
         });
-        vector_lc.push(lc);
+        let message = Root::create(&mut builder, &RootArgs {
+            message_type: Message::Witness,
+            message: Some(assign.as_union_value()),
+        });
+        builder.finish_size_prefixed(message, None);
+
+        builder.finished_data().to_vec()
     }
 
-    let vector_offset = builder.create_vector(vector_lc.as_slice());
+    /// Build a `Circuit` message connecting the variables `ONE_WIRE_ID..first_local_id`, optionally with their values
+    pub fn circuit_return(
+        &self,
+        first_local_id: u64,
+        free_variable_id: u64,
+        public_inputs: Option<&[FieldPrime]>,
+        r1cs_generation: bool,
+    ) -> Vec<u8> {
+        // Convert element representations.
+        let values = public_inputs.map(|public_inputs| {
+            assert_eq!(ONE_WIRE_ID + public_inputs.len() as u64, first_local_id);
+            let mut values = vec![];
+            for value in public_inputs {
+                values.append(&mut encode_field_element(value));
+            }
+            values
+        });
 
-    let args = R1CSConstraintsArgs { constraints: Some(vector_offset), info: None };
+        let gadget_return = CircuitOwned {
+            connections: VariablesOwned {
+                variable_ids: (ONE_WIRE_ID..first_local_id).collect(),
+                values,
+            },
+            free_variable_id,
+            r1cs_generation,
+            field_maximum: Some(encode_field_element(&FieldPrime::max_value())),
+        };
+
+        let mut out = vec![];
+        gadget_return.write(&mut out).unwrap();
+        out
+    }
+}
+
+fn convert_constraint<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    a: &LinearCombination,
+    b: &LinearCombination,
+    c: &LinearCombination,
+) -> WIPOffset<BilinearConstraint<'a>> {
+    let a_var_val = convert_linear_combination(builder, a);
+    let b_var_val = convert_linear_combination(builder, b);
+    let c_var_val = convert_linear_combination(builder, c);
+
+    BilinearConstraint::create(builder, &BilinearConstraintArgs {
+        linear_combination_a: Some(a_var_val),
+        linear_combination_b: Some(b_var_val),
+        linear_combination_c: Some(c_var_val),
+    })
+}
+
+// labels are attached in the `info` of the message, keyed by the index of their constraint
+fn finish_r1cs<'a>(
+    mut builder: FlatBufferBuilder<'a>,
+    constraints: Vec<WIPOffset<BilinearConstraint<'a>>>,
+    labels: Option<&[String]>,
+) -> Vec<u8> {
+    let info = labels.map(|labels| {
+        assert_eq!(labels.len(), constraints.len(), "expected one label per constraint");
+
+        let key_values: Vec<_> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let key = builder.create_string(&i.to_string());
+                let value = builder.create_vector(label.as_bytes());
+                KeyValue::create(&mut builder, &KeyValueArgs { key: Some(key), value: Some(value) })
+            })
+            .collect();
+
+        builder.create_vector(key_values.as_slice())
+    });
+
+    let vector_offset = builder.create_vector(constraints.as_slice());
+
+    let args = R1CSConstraintsArgs { constraints: Some(vector_offset), info };
 
     let r1cs_constraints = R1CSConstraints::create(&mut builder, &args);
     let root_args = RootArgs { message_type: Message::R1CSConstraints, message: Some(r1cs_constraints.as_union_value()) };
@@ -137,19 +632,17 @@ fn write_r1cs<W: Write>(
 
     builder.finish_size_prefixed(root, None);
 
-    out_file.write_all(builder.finished_data()).unwrap();
+    builder.finished_data().to_vec()
 }
 
-fn convert_linear_combination<'a>(builder: &mut FlatBufferBuilder<'a>, item: &Vec<(usize, FieldPrime)>) -> (WIPOffset<Variables<'a>>) {
+fn convert_linear_combination<'a>(builder: &mut FlatBufferBuilder<'a>, item: &LinearCombination) -> (WIPOffset<Variables<'a>>) {
     let mut variable_ids: Vec<u64> = Vec::new();
     let mut values: Vec<u8> = Vec::new();
 
-    for i in 0..item.len() {
-        variable_ids.push(item[i].0 as u64);
+    for (id, value) in canonical_linear_combination(item) {
+        variable_ids.push(id as u64);
 
-        let mut bytes = item[i].1.into_byte_vector();
-        bytes.resize(FIELD_LENGTH, 0);
-        values.append(&mut bytes);
+        values.append(&mut encode_field_element(&value));
     }
 
     let variable_ids = Some(builder.create_vector(&variable_ids));
@@ -161,94 +654,61 @@ fn convert_linear_combination<'a>(builder: &mut FlatBufferBuilder<'a>, item: &Ve
     })
 }
 
-fn write_assignment<W: Write>(
-    first_local_id: u64,
-    local_values: &[FieldPrime],
-    out_file: &mut W,
-) {
-    let mut builder = &mut FlatBufferBuilder::new();
-
-    let mut ids = vec![];
-    let mut values = vec![];
-
-    for i in 0..local_values.len() {
-        ids.push(first_local_id + i as u64);
-
-        let mut bytes = local_values[i].into_byte_vector();
-        bytes.resize(FIELD_LENGTH, 0);
-        values.append(&mut bytes);
-    }
-
-    let ids = builder.create_vector(&ids);
-    let values = builder.create_vector(&values);
-    let values = Variables::create(&mut builder, &VariablesArgs {
-        variable_ids: Some(ids),
-        values: Some(values),
-        info: None,
-    });
-    let assign = Witness::create(&mut builder, &WitnessArgs {
-        assigned_variables: Some(values),
-    });
-    let message = Root::create(&mut builder, &RootArgs {
-        message_type: Message::Witness,
-        message: Some(assign.as_union_value()),
-    });
-    builder.finish_size_prefixed(message, None);
-
-    out_file.write_all(builder.finished_data()).unwrap();
+// little-endian representation of `value`, with the byte width of its field
+fn encode_field_element<T: Field>(value: &T) -> Vec<u8> {
+    to_byte_width(value.into_byte_vector(), T::byte_width())
 }
 
+fn to_byte_width(mut bytes: Vec<u8>, width: usize) -> Vec<u8> {
+    bytes.resize(width, 0);
+    bytes
+}
 
-fn write_circuit<W: Write>(
-    first_local_id: u64,
-    free_variable_id: u64,
-    public_inputs: Option<&[FieldPrime]>,
-    r1cs_generation: bool,
-    out_file: &mut W,
-) {
-    // Convert element representations.
-    let values = public_inputs.map(|public_inputs| {
-        assert_eq!(public_inputs.len() as u64, first_local_id);
-        let mut values = vec![];
-        for value in public_inputs {
-            let mut bytes = value.into_byte_vector();
-            bytes.resize(FIELD_LENGTH, 0);
-            values.append(&mut bytes);
-        }
-        values
-    });
+// sort the terms by variable id and sum the values of duplicate ids, so that the serialized bytes do not depend on term order
+fn canonical_linear_combination(item: &LinearCombination) -> Vec<(usize, FieldPrime)> {
+    let mut terms: BTreeMap<usize, FieldPrime> = BTreeMap::new();
 
-    let gadget_return = CircuitOwned {
-        connections: VariablesOwned {
-            variable_ids: (0..first_local_id).collect(),
-            values,
-        },
-        free_variable_id,
-        r1cs_generation,
-        field_maximum: None,
-    };
+    for (id, value) in item.terms() {
+        let sum = match terms.remove(id) {
+            Some(previous) => previous + value.clone(),
+            None => value.clone(),
+        };
+        terms.insert(*id, sum);
+    }
 
-    gadget_return.write(out_file).unwrap();
+    terms.into_iter().collect()
 }
 
-
 fn prepare_generate_proof<T: Field>(
     program: ir::Prog<T>,
     witness: ir::Witness<T>,
-) -> (Vec<T>, Vec<T>) {
+) -> Result<(Vec<T>, Vec<T>), String> {
     // recover variable order from the program
     let (variables, public_variables_count, _, _, _) = r1cs_program(program);
 
-    let witness: Vec<T> = variables.iter().map(|x| witness.0[x].clone()).collect();
+    let mut public_inputs: Vec<T> = variables
+        .iter()
+        .filter_map(|x| witness.0.get(x).cloned())
+        .collect();
+
+    // the assignment must have a value for each variable the circuit declares
+    if public_inputs.len() != variables.len() {
+        let missing = variables.iter().find(|x| !witness.0.contains_key(x)).unwrap();
+        return Err(format!(
+            "the witness assigns {} of the {} variables of the constraint system, {} is missing",
+            public_inputs.len(),
+            variables.len(),
+            missing
+        ));
+    }
 
     // split witness into public and private inputs at offset
-    let mut public_inputs: Vec<T> = witness.clone();
     let private_inputs: Vec<T> = public_inputs.split_off(public_variables_count);
 
-    (
+    Ok((
         public_inputs,
         private_inputs,
-    )
+    ))
 }
 
 fn provide_variable_idx(
@@ -269,7 +729,8 @@ fn r1cs_program<T: Field>(
     Vec<Vec<(usize, T)>>,
 ) {
     let mut variables: HashMap<FlatVariable, usize> = HashMap::new();
-    provide_variable_idx(&mut variables, &FlatVariable::one());
+    let one = provide_variable_idx(&mut variables, &FlatVariable::one());
+    assert_eq!(one as u64, ONE_WIRE_ID);
 
     for x in prog
         .main
@@ -281,6 +742,8 @@ fn r1cs_program<T: Field>(
             provide_variable_idx(&mut variables, &x.1);
         }
 
+    let input_count = variables.len() - 1;
+
     //Only the main function is relevant in this step, since all calls to other functions were resolved during flattening
     let main = prog.main;
 
@@ -289,7 +752,8 @@ fn r1cs_program<T: Field>(
     let main_return_count = main.returns.len();
 
     for i in 0..main_return_count {
-        provide_variable_idx(&mut variables, &FlatVariable::public(i));
+        let id = provide_variable_idx(&mut variables, &FlatVariable::public(i));
+        assert_eq!(id as u64, first_output_id(input_count) + i as u64);
     }
 
     // position where private part of witness starts
@@ -355,18 +819,35 @@ fn r1cs_program<T: Field>(
 #[cfg(test)]
 mod tests {
     use crate::compile::compile;
+    use crate::flat_absy::{FlatExpression, FlatExpressionList, FlatFunction, FlatParameter, FlatStatement};
     use crate::imports::Error;
-    use super::{FIELD_LENGTH, generate_proof, setup};
+    use crate::types::{Signature, Type};
+    use flat_absy::flat_variable::FlatVariable;
+    use flat_absy::FlatProg;
+    use ir;
+    use proof_system::{LinearCombination, ProofSystem, SetupResult, VerificationError, VerifierExportError};
+    use std::fs::File;
+    use std::io::BufReader;
+    use super::{FIELD_LENGTH, FIRST_INPUT_ID, InterfaceDescription, ONE_WIRE_ID, Writer, ZkInterface, canonical_linear_combination, constraint_labels, constraint_system_hash, encode_field_element, first_output_id, from_flat_prog, generate_proof, generate_proof_with_values, r1cs_program, r1cs_variables, setup, setup_from_source, setup_with_result, to_byte_width, verify, write_circuit_header};
     use zkinterface::reading::{Constraint, Messages, Term, Variable};
+    use zkinterface::zkinterface_generated::zkinterface::Message;
     use zokrates_field::field::{Field, FieldPrime};
 
     fn encode(x: u8) -> [u8; 32] {
         return [x, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
     }
 
+    /// Runs `write` twice and checks that both runs produce the same bytes, so that the output can be cached
+    fn assert_reproducible<F: Fn() -> Vec<u8>>(write: F) -> Vec<u8> {
+        let first = write();
+        let second = write();
+        assert_eq!(first, second, "zkinterface output differs between two runs");
+        first
+    }
+
     #[test]
     fn test_zkinterface() {
-        assert!(FieldPrime::get_required_bits() < FIELD_LENGTH * 8);
+        assert!(FieldPrime::bits() < FIELD_LENGTH * 8);
         let empty = &[] as &[u8];
         let one = &encode(1);
         let minus_one = &[0, 0, 0, 240, 147, 245, 225, 67, 145, 112, 185, 121, 72, 232, 51, 40, 93, 88, 129, 129, 182, 69, 80, 184, 41, 160, 49, 225, 114, 78, 100, 48 as u8];
@@ -422,7 +903,7 @@ mod tests {
                 },
                 Constraint {
                     a: vec![Term { id: 0, value: one }], // 1
-                    b: vec![Term { id: 3, value: one }, Term { id: 5, value: one }, Term { id: 0, value: minus_one }], // xx + yy - 1
+                    b: vec![Term { id: 0, value: minus_one }, Term { id: 3, value: one }, Term { id: 5, value: one }], // -1 + xx + yy
                     c: vec![Term { id: 2, value: one }], // return
                 },
             ]);
@@ -461,4 +942,660 @@ mod tests {
             ]);
         }
     }
+    #[test]
+    fn test_combined_stream() {
+        let code = "
+            def main(field x, private field y) -> (field):
+                return x * y
+        ";
+
+        let program = compile::<FieldPrime, &[u8], &[u8], Error>(
+            &mut code.as_bytes(), None, None).unwrap();
+        let witness = program
+            .clone()
+            .execute::<FieldPrime>(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let mut buf = Vec::<u8>::new();
+        assert!(ZkInterface::write_combined_stream(&mut buf, program, witness));
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        let types: Vec<_> = messages.into_iter().map(|root| root.message_type()).collect();
+        assert_eq!(types, vec![
+            Message::Circuit,         // header
+            Message::R1CSConstraints,
+            Message::Circuit,         // public values
+            Message::Witness,
+        ]);
+
+        assert!(verify(&messages, &[FieldPrime::from(3), FieldPrime::from(12)]));
+    }
+
+    #[test]
+    fn test_verify() {
+        let code = "
+            def main(field x, private field y) -> (field):
+                return x * y
+        ";
+
+        let program = compile::<FieldPrime, &[u8], &[u8], Error>(
+            &mut code.as_bytes(), None, None).unwrap();
+
+        let witness = program
+            .clone()
+            .execute::<FieldPrime>(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let mut buf = Vec::<u8>::new();
+        setup(program.clone(), &mut buf);
+        generate_proof(program, witness, &mut buf);
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        // public input x and output x * y
+        assert!(verify(&messages, &[FieldPrime::from(3), FieldPrime::from(12)]));
+        assert!(!verify(&messages, &[FieldPrime::from(3), FieldPrime::from(13)]));
+    }
+
+    #[test]
+    fn test_setup_then_verify() {
+        let code = "
+            def main(field x, private field y) -> (field):
+                return x * y
+        ";
+
+        let program = compile::<FieldPrime, &[u8], &[u8], Error>(
+            &mut code.as_bytes(), None, None).unwrap();
+
+        let witness = program
+            .clone()
+            .execute::<FieldPrime>(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        let dir = std::env::temp_dir();
+        let path = |name: &str| dir.join(format!("zkinterface_setup_then_verify.{}", name)).to_str().unwrap().to_string();
+        let (pk_path, vk_path, proof_path) = (path("pk"), path("vk"), path("proof"));
+
+        let scheme = ZkInterface::new();
+        scheme.setup(program.clone(), &pk_path, &vk_path);
+        assert!(scheme.generate_proof(program, witness, &pk_path, &proof_path));
+
+        // public input x and output x * y
+        assert_eq!(scheme.verify(&vk_path, &proof_path, &[FieldPrime::from(3), FieldPrime::from(12)]), Ok(true));
+        assert_eq!(scheme.verify(&vk_path, &proof_path, &[FieldPrime::from(3), FieldPrime::from(13)]), Ok(false));
+
+        // a missing proof is an error rather than a panic
+        match scheme.verify(&vk_path, &path("missing"), &[FieldPrime::from(3), FieldPrime::from(12)]) {
+            Err(VerificationError::MalformedArtifact(_)) => {}
+            r => panic!("expected a malformed artifact, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_verify_unsatisfied() {
+        // x * x == y
+        let a = vec![LinearCombination::new().add_term(1, FieldPrime::from(1))];
+        let b = vec![LinearCombination::new().add_term(1, FieldPrime::from(1))];
+        let c = vec![LinearCombination::new().add_term(2, FieldPrime::from(1))];
+
+        let writer = Writer::new();
+
+        let mut buf = Vec::<u8>::new();
+        write_circuit_header(3, &[0, 1], &FieldPrime::max_value(), &mut buf);
+        buf.extend(writer.r1cs(&a, &b, &c));
+        // x = 2, y = 5
+        generate_proof_with_values(&[FieldPrime::from(1), FieldPrime::from(2)], &[FieldPrime::from(5)], &mut buf);
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        assert!(!verify(&messages, &[FieldPrime::from(2)]));
+    }
+
+    #[test]
+    fn test_export_solidity_verifier() {
+        // zkinterface has no verification key, any file stands for one
+        let reader = BufReader::new(File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap());
+
+        assert_eq!(
+            ZkInterface::new().export_solidity_verifier(reader),
+            Err(VerifierExportError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_check_witness() {
+        // x * x == y
+        // 2 * y == y + y
+        let a = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::constant(FieldPrime::from(2)),
+        ];
+        let b = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+        ];
+        let c = vec![
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(2)),
+        ];
+
+        // one, x = 3, y = 9
+        let assignment = vec![FieldPrime::from(1), FieldPrime::from(3), FieldPrime::from(9)];
+        assert_eq!(ZkInterface::check_witness(&a, &b, &c, &assignment), Ok(()));
+
+        // y = 10 breaks the first constraint only
+        let assignment = vec![FieldPrime::from(1), FieldPrime::from(3), FieldPrime::from(10)];
+        assert_eq!(ZkInterface::check_witness(&a, &b, &c, &assignment), Err(0));
+
+        // the one wire is 2, which breaks the second constraint only
+        let assignment = vec![FieldPrime::from(2), FieldPrime::from(3), FieldPrime::from(9)];
+        assert_eq!(ZkInterface::check_witness(&a, &b, &c, &assignment), Err(1));
+
+        // y is missing
+        let assignment = vec![FieldPrime::from(1), FieldPrime::from(3)];
+        assert_eq!(ZkInterface::check_witness(&a, &b, &c, &assignment), Err(0));
+    }
+
+    #[test]
+    fn test_from_flat_prog() {
+        // def main(x, private y) -> (field):
+        //   z = x * y
+        //   return z * z
+        let x = FlatVariable::new(0);
+        let y = FlatVariable::new(1);
+        let z = FlatVariable::new(2);
+
+        let program: FlatProg<FieldPrime> = FlatProg {
+            functions: vec![FlatFunction {
+                id: String::from("main"),
+                arguments: vec![
+                    FlatParameter { id: x, private: false },
+                    FlatParameter { id: y, private: true },
+                ],
+                statements: vec![
+                    FlatStatement::Definition(
+                        z,
+                        FlatExpression::Mult(
+                            box FlatExpression::Identifier(x),
+                            box FlatExpression::Identifier(y),
+                        ),
+                    ),
+                    FlatStatement::Return(FlatExpressionList {
+                        expressions: vec![FlatExpression::Mult(
+                            box FlatExpression::Identifier(z),
+                            box FlatExpression::Identifier(z),
+                        )],
+                    }),
+                ],
+                signature: Signature::new()
+                    .inputs(vec![Type::FieldElement, Type::FieldElement])
+                    .outputs(vec![Type::FieldElement]),
+            }],
+        };
+
+        let mut buf = Vec::<u8>::new();
+        from_flat_prog(program, &mut buf);
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        // z = x * y, ~out_0 = z * z
+        assert_eq!(messages.iter_constraints().count(), 2);
+    }
+
+    #[test]
+    fn test_writer() {
+        let one = &encode(1);
+        let two = &encode(2);
+
+        // one public variable x, one private variable y
+        // x * x == y
+        // 2 * y == y + y
+        let a = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::constant(FieldPrime::from(2)),
+        ];
+        let b = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+        ];
+        let c = vec![
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(2)),
+        ];
+
+        let writer = Writer::new();
+
+        let mut buf = Vec::<u8>::new();
+        buf.extend(writer.circuit_return(2, 3, Some(&[FieldPrime::from(1), FieldPrime::from(2)]), true));
+        buf.extend(writer.r1cs(&a, &b, &c));
+        buf.extend(writer.assignment(2, &[FieldPrime::from(4)]));
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+        assert_eq!(messages.into_iter().count(), 3);
+
+        let circuit = messages.last_circuit().unwrap();
+        assert_eq!(circuit.free_variable_id(), 3);
+
+        let pub_vars = messages.connection_variables().unwrap();
+        assert_eq!(pub_vars, vec![
+            Variable { id: 0, value: one }, // one
+            Variable { id: 1, value: two }, // x
+        ]);
+
+        let pri_vars = messages.private_variables().unwrap();
+        assert_eq!(pri_vars, vec![
+            Variable { id: 2, value: &encode(4) }, // y
+        ]);
+
+        let cs: Vec<_> = messages.iter_constraints().collect();
+        assert_eq!(cs, vec![
+            Constraint {
+                a: vec![Term { id: 1, value: one }], // x
+                b: vec![Term { id: 1, value: one }], // x
+                c: vec![Term { id: 2, value: one }], // y
+            },
+            Constraint {
+                a: vec![Term { id: 0, value: two }], // 2
+                b: vec![Term { id: 2, value: one }], // y
+                c: vec![Term { id: 2, value: two }], // 2 * y
+            },
+        ]);
+    }
+    #[test]
+    fn test_generate_proof_with_values() {
+        let public_inputs = vec![FieldPrime::from(1), FieldPrime::from(3)];
+        let local_values = vec![FieldPrime::from(9), FieldPrime::from(4)];
+
+        let mut buf = Vec::<u8>::new();
+        assert!(generate_proof_with_values(&public_inputs, &local_values, &mut buf));
+
+        // same bytes as writing the messages from the owned values
+        let writer = Writer::new();
+        let mut expected = writer.circuit_return(2, 4, Some(&public_inputs), false);
+        expected.extend(writer.assignment(2, &local_values));
+
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let code = "
+            def main(field x, private field y) -> (field):
+                field xx = x * x
+                field yy = y * y
+                return xx + yy - 1
+        ";
+
+        let program = compile::<FieldPrime, &[u8], &[u8], Error>(
+            &mut code.as_bytes(), None, None).unwrap();
+
+        // the whole setup, from the program to the messages
+        assert_reproducible(|| {
+            let mut buf = Vec::<u8>::new();
+            setup(program.clone(), &mut buf);
+            buf
+        });
+
+        // each message, from the same (variables, a, b, c)
+        let (variables, first_local_id, a, b, c) = r1cs_program(program.clone());
+        let to_lcs = |rows: Vec<Vec<(usize, FieldPrime)>>| rows.into_iter().map(LinearCombination::from).collect::<Vec<_>>();
+        let (a, b, c) = (to_lcs(a), to_lcs(b), to_lcs(c));
+        let writer = Writer::new();
+
+        assert_reproducible(|| writer.r1cs(&a, &b, &c));
+        assert_reproducible(|| writer.circuit_return(first_local_id as u64, variables.len() as u64, None, true));
+
+        let witness = program
+            .clone()
+            .execute::<FieldPrime>(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+
+        assert_reproducible(|| {
+            let mut buf = Vec::<u8>::new();
+            generate_proof(program.clone(), witness.clone(), &mut buf);
+            buf
+        });
+    }
+
+    #[test]
+    fn test_setup_from_source() {
+        // x * x == y
+        // 2 * y == y + y
+        let a = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::constant(FieldPrime::from(2)),
+        ];
+        let b = vec![
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+        ];
+        let c = vec![
+            LinearCombination::new().add_term(2, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(2)),
+        ];
+
+        let writer = Writer::new();
+        let mut expected = vec![];
+        write_circuit_header(3, &[0, 1], &FieldPrime::max_value(), &mut expected);
+        expected.extend(writer.r1cs(&a, &b, &c));
+
+        // the constraints are produced one at a time, without holding the matrices
+        let mut source = (0..2).map(|i| (a[i].clone(), b[i].clone(), c[i].clone()));
+
+        let mut buf = Vec::<u8>::new();
+        setup_from_source(&mut source, 2, 3, None, &mut buf);
+
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_constraint_system_hash() {
+        // x * x + 2 * y == y + 3
+        let constraints = vec![(
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(1, FieldPrime::from(1)).add_term(2, FieldPrime::from(2)),
+            LinearCombination::constant(FieldPrime::from(3)).add_term(2, FieldPrime::from(1)),
+        )];
+        // the same constraint with its terms in another order
+        let reordered = vec![(
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(2)).add_term(1, FieldPrime::from(1)),
+            LinearCombination::new().add_term(2, FieldPrime::from(1)).add_term(0, FieldPrime::from(3)),
+        )];
+        // a different constraint with the same terms
+        let swapped = vec![(
+            LinearCombination::new().add_term(1, FieldPrime::from(1)).add_term(2, FieldPrime::from(2)),
+            LinearCombination::new().add_term(1, FieldPrime::from(1)),
+            LinearCombination::constant(FieldPrime::from(3)).add_term(2, FieldPrime::from(1)),
+        )];
+
+        let hash = constraint_system_hash(&mut constraints.clone().into_iter());
+
+        assert_eq!(hash.len(), 32);
+        assert_eq!(constraint_system_hash(&mut reordered.into_iter()), hash);
+        assert!(constraint_system_hash(&mut swapped.into_iter()) != hash);
+
+        // setup returns the same hash
+        let mut buf = Vec::<u8>::new();
+        assert_eq!(setup_from_source(&mut constraints.into_iter(), 1, 3, None, &mut buf), hash);
+    }
+
+    #[test]
+    fn test_first_output_id() {
+        // def main(field x, field y) -> (field)
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+            },
+            private: vec![false, false],
+        };
+
+        // one, x, y, ~out_0
+        assert_eq!(first_output_id(2), 3);
+
+        let (variables, first_local_id, _, _, _) = r1cs_program(program);
+        assert_eq!(variables[ONE_WIRE_ID as usize], FlatVariable::one());
+        assert_eq!(variables[FIRST_INPUT_ID as usize], FlatVariable::new(0));
+        assert_eq!(variables[first_output_id(2) as usize], FlatVariable::public(0));
+        assert_eq!(first_local_id as u64, first_output_id(2) + 1);
+    }
+
+    #[test]
+    fn test_describe_interface() {
+        // def main(field x, private field y) -> (field): return y
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![ir::Statement::definition(FlatVariable::public(0), FlatVariable::new(1))],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+            },
+            private: vec![false, true],
+        };
+
+        // one, x, ~out_0 | y
+        let (variables, public_count, _, _, _) = r1cs_program(program);
+        let interface = ZkInterface::describe_interface(variables.len(), public_count);
+
+        assert_eq!(
+            interface,
+            InterfaceDescription {
+                public_input_ids: vec![ONE_WIRE_ID, FIRST_INPUT_ID, first_output_id(1)],
+                first_local_id: 3,
+                free_variable_id: 4,
+            }
+        );
+        assert_eq!(variables[interface.first_local_id as usize], FlatVariable::new(1));
+    }
+
+    #[test]
+    fn test_variable_id_map() {
+        // def main(field x, private field y) -> (field): return y
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![ir::Statement::definition(FlatVariable::public(0), FlatVariable::new(1))],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+            },
+            private: vec![false, true],
+        };
+
+        let ids = ZkInterface::variable_id_map(&r1cs_variables(program.clone()));
+
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[&FlatVariable::one()], ONE_WIRE_ID);
+        assert_eq!(ids[&FlatVariable::new(0)], FIRST_INPUT_ID);
+        assert_eq!(ids[&FlatVariable::public(0)], first_output_id(1));
+        assert_eq!(ids[&FlatVariable::new(1)], 3);
+
+        // the constraint 1 * y == ~out_0 refers to the variables by the same ids
+        let (_, _, a, b, c) = r1cs_program(program);
+        let terms: Vec<u64> = a[0].iter().chain(b[0].iter()).chain(c[0].iter()).map(|(id, _)| *id as u64).collect();
+        assert_eq!(terms, vec![ids[&FlatVariable::one()], ids[&FlatVariable::new(1)], ids[&FlatVariable::public(0)]]);
+    }
+
+    #[test]
+    fn test_setup_with_result() {
+        // def main(field x, private field y) -> (field): return y
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![ir::Statement::definition(FlatVariable::public(0), FlatVariable::new(1))],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+            },
+            private: vec![false, true],
+        };
+
+        let mut buf = Vec::<u8>::new();
+        let result = setup_with_result(program.clone(), &mut buf);
+
+        // the messages and the hash are those of `setup`
+        let mut expected = Vec::<u8>::new();
+        let hash = setup(program, &mut expected);
+        assert_eq!(buf, expected);
+
+        // one, x, ~out_0 | y, constrained by ~out_0 == y
+        assert_eq!(
+            result,
+            SetupResult {
+                num_constraints: 1,
+                num_variables: 4,
+                first_output_id: 2,
+                constraint_system_hash: hash,
+            }
+        );
+    }
+
+    #[test]
+    fn test_generate_proof_truncated_witness() {
+        // def main(field x, private field y) -> (field): return y
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![ir::Statement::definition(FlatVariable::public(0), FlatVariable::new(1))],
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+            },
+            private: vec![false, true],
+        };
+
+        // the value of y is missing
+        let witness = ir::Witness(
+            vec![
+                (FlatVariable::one(), FieldPrime::from(1)),
+                (FlatVariable::new(0), FieldPrime::from(2)),
+                (FlatVariable::public(0), FieldPrime::from(3)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut buf = Vec::<u8>::new();
+        assert!(!generate_proof(program, witness, &mut buf));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_constraint_labels() {
+        // _42 = _41
+        let program: ir::Prog<FieldPrime> = ir::Prog {
+            main: ir::Function {
+                id: String::from("main"),
+                statements: vec![ir::Statement::definition(FlatVariable::new(42), FlatVariable::new(41))],
+                arguments: vec![],
+                returns: vec![],
+            },
+            private: vec![],
+        };
+
+        assert_eq!(
+            constraint_labels(&program),
+            vec![String::from("(1 * ~one) * (1 * _41) == 1 * _42")]
+        );
+    }
+
+    #[test]
+    fn test_r1cs_labels() {
+        // x * x == y
+        let a = vec![LinearCombination::new().add_term(1, FieldPrime::from(1))];
+        let b = vec![LinearCombination::new().add_term(1, FieldPrime::from(1))];
+        let c = vec![LinearCombination::new().add_term(2, FieldPrime::from(1))];
+
+        let writer = Writer::new();
+
+        // without labels, the message is unchanged
+        assert_eq!(writer.r1cs_with_labels(&a, &b, &c, None), writer.r1cs(&a, &b, &c));
+
+        let labels = vec![String::from("x * x == y")];
+        let buf = writer.r1cs_with_labels(&a, &b, &c, Some(&labels));
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        let r1cs = messages.into_iter().next().unwrap().message_as_r1csconstraints().unwrap();
+        let info = r1cs.info().unwrap();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info.get(0).key(), Some("0"));
+        assert_eq!(info.get(0).value(), Some("x * x == y".as_bytes()));
+    }
+
+    #[test]
+    fn test_circuit_header() {
+        let mut buf = Vec::<u8>::new();
+        write_circuit_header(6, &[0, 1, 2], &FieldPrime::max_value(), &mut buf);
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+        assert_eq!(messages.into_iter().count(), 1);
+
+        let circuit = messages.last_circuit().unwrap();
+        assert_eq!(circuit.free_variable_id(), 6);
+        assert!(circuit.r1cs_generation());
+        assert_eq!(
+            circuit.field_maximum(),
+            Some(&encode_field_element(&FieldPrime::max_value())[..])
+        );
+
+        let empty = &[] as &[u8];
+        let pub_vars = messages.connection_variables().unwrap();
+        assert_eq!(pub_vars, vec![
+            Variable { id: 0, value: empty },
+            Variable { id: 1, value: empty },
+            Variable { id: 2, value: empty },
+        ]);
+    }
+
+    #[test]
+    fn test_byte_width() {
+        assert_eq!(encode_field_element(&FieldPrime::from(1)), encode(1).to_vec());
+
+        // a hypothetical 64-bit field
+        assert_eq!(
+            to_byte_width(FieldPrime::from(258).into_byte_vector(), 8),
+            vec![2, 1, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_linear_combination() {
+        let one = &encode(1);
+        let two = &encode(2);
+
+        // 2 + x + y + y
+        let lc = LinearCombination::constant(FieldPrime::from(2))
+            .add_term(2, FieldPrime::from(1))
+            .add_term(1, FieldPrime::from(1))
+            .add_term(2, FieldPrime::from(1));
+
+        assert_eq!(lc.terms(), &[
+            (0, FieldPrime::from(2)),
+            (2, FieldPrime::from(1)),
+            (1, FieldPrime::from(1)),
+            (2, FieldPrime::from(1)),
+        ][..]);
+
+        // serialized with sorted and merged terms
+        let writer = Writer::new();
+        let buf = writer.r1cs(&[lc.clone()], &[LinearCombination::constant(FieldPrime::from(1))], &[lc]);
+
+        let mut messages = Messages::new(0);
+        messages.push_message(buf).unwrap();
+
+        let cs: Vec<_> = messages.iter_constraints().collect();
+        assert_eq!(cs, vec![
+            Constraint {
+                a: vec![Term { id: 0, value: two }, Term { id: 1, value: one }, Term { id: 2, value: two }],
+                b: vec![Term { id: 0, value: one }],
+                c: vec![Term { id: 0, value: two }, Term { id: 1, value: one }, Term { id: 2, value: two }],
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_canonical_linear_combination() {
+        let lc: LinearCombination = vec![
+            (3, FieldPrime::from(1)),
+            (0, FieldPrime::from(2)),
+            (3, FieldPrime::from(4)),
+            (1, FieldPrime::from(5)),
+            (0, FieldPrime::from(6)),
+        ].into();
+
+        assert_eq!(canonical_linear_combination(&lc), vec![
+            (0, FieldPrime::from(8)),
+            (1, FieldPrime::from(5)),
+            (3, FieldPrime::from(5)),
+        ]);
+    }
 }
@@ -309,7 +309,7 @@ impl<'ast> Checker<'ast> {
                     .collect();
 
                 match return_statement_types == *header_return_types {
-                    true => Ok(TypedStatement::Return(expression_list_checked)),
+                    true => Ok(TypedStatement::Return(expression_list_checked, Some(pos))),
                     false => Err(Error {
                         pos: Some(pos),
                         message: format!(
@@ -329,7 +329,7 @@ impl<'ast> Checker<'ast> {
                 }
             }
             Statement::Declaration(var) => match self.insert_scope(var.clone().value) {
-                true => Ok(TypedStatement::Declaration(var.value.into())),
+                true => Ok(TypedStatement::Declaration(var.value.into(), Some(pos))),
                 false => Err(Error {
                     pos: Some(pos),
                     message: format!("Duplicate declaration for variable named {}", var.value.id),
@@ -354,7 +354,7 @@ impl<'ast> Checker<'ast> {
 
                 // make sure the assignee has the same type as the rhs
                 match var_type == expression_type {
-                    true => Ok(TypedStatement::Definition(var, checked_expr)),
+                    true => Ok(TypedStatement::Definition(var, checked_expr, Some(pos))),
                     false => Err(Error {
                         pos: Some(pos),
                         message: format!(
@@ -364,25 +364,35 @@ impl<'ast> Checker<'ast> {
                     }),
                 }
             }
-            Statement::Condition(lhs, rhs) => {
+            Statement::Condition(lhs, rhs, message) => {
                 let checked_lhs = self.check_expression(lhs)?;
                 let checked_rhs = self.check_expression(rhs)?;
 
-                match (checked_lhs.clone(), checked_rhs.clone()) {
-                    (ref l, ref r) if r.get_type() == l.get_type() => {
-                        Ok(TypedStatement::Condition(checked_lhs, checked_rhs))
+                // user assertions are lowered to `Assert`, conditions are left to the compiler
+                let assertion = match (checked_lhs, checked_rhs) {
+                    (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
+                        Ok(BooleanExpression::Eq(box e1, box e2))
+                    }
+                    (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
+                        Ok(BooleanExpression::BoolEq(box e1, box e2))
                     }
+                    (
+                        TypedExpression::FieldElementArray(e1),
+                        TypedExpression::FieldElementArray(e2),
+                    ) if e1.size() == e2.size() => Ok(BooleanExpression::ArrayEq(box e1, box e2)),
                     (e1, e2) => Err(Error {
                         pos: Some(pos),
                         message: format!(
                             "Cannot compare {} of type {:?} to {} of type {:?}",
-                            checked_lhs,
+                            e1,
                             e1.get_type(),
-                            checked_rhs,
+                            e2,
                             e2.get_type(),
                         ),
                     }),
-                }
+                }?;
+
+                Ok(TypedStatement::Assert(assertion, message, Some(pos)))
             }
             Statement::For(var, from, to, statements) => {
                 self.enter_scope();
@@ -404,6 +414,7 @@ impl<'ast> Checker<'ast> {
                     from,
                     to,
                     checked_statements,
+                    Some(pos),
                 ))
             }
             Statement::MultipleDefinition(assignees, rhs) => {
@@ -459,7 +470,7 @@ impl<'ast> Checker<'ast> {
                                     self.insert_scope(var);
                                 }
 
-                                Ok(TypedStatement::MultipleDefinition(assignees, call))
+                                Ok(TypedStatement::MultipleDefinition(assignees, call, Some(pos)))
                     		},
                     		0 => Err(Error {                         pos: Some(pos),
  message: format!("Function definition for function {} with signature {} not found.", fun_id, query) }),
@@ -640,6 +651,44 @@ impl<'ast> Checker<'ast> {
                     }),
                 }
             }
+            Expression::Max(box e1, box e2) => {
+                let e1_checked = self.check_expression(e1)?;
+                let e2_checked = self.check_expression(e2)?;
+
+                match (e1_checked, e2_checked) {
+                    (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => Ok(
+                        TypedExpression::FieldElement(FieldElementExpression::Max(box e1, box e2)),
+                    ),
+                    (t1, t2) => Err(Error {
+                        pos: Some(pos),
+
+                        message: format!(
+                            "Expected only field elements, found {:?}, {:?}",
+                            t1.get_type(),
+                            t2.get_type()
+                        ),
+                    }),
+                }
+            }
+            Expression::Min(box e1, box e2) => {
+                let e1_checked = self.check_expression(e1)?;
+                let e2_checked = self.check_expression(e2)?;
+
+                match (e1_checked, e2_checked) {
+                    (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => Ok(
+                        TypedExpression::FieldElement(FieldElementExpression::Min(box e1, box e2)),
+                    ),
+                    (t1, t2) => Err(Error {
+                        pos: Some(pos),
+
+                        message: format!(
+                            "Expected only field elements, found {:?}, {:?}",
+                            t1.get_type(),
+                            t2.get_type()
+                        ),
+                    }),
+                }
+            }
             Expression::IfElse(box condition, box consequence, box alternative) => {
                 let condition_checked = self.check_expression(condition)?;
                 let consequence_checked = self.check_expression(consequence)?;
@@ -657,6 +706,9 @@ impl<'ast> Checker<'ast> {
                                 (TypedExpression::FieldElementArray(consequence), TypedExpression::FieldElementArray(alternative)) => {
                                     Ok(FieldElementArrayExpression::IfElse(box condition, box consequence, box alternative).into())
                                 },
+                                (TypedExpression::Boolean(consequence), TypedExpression::Boolean(alternative)) => {
+                                    Ok(BooleanExpression::IfElse(box condition, box consequence, box alternative).into())
+                                },
                                 _ => unimplemented!()
                             }
                             false => Err(Error {
@@ -706,6 +758,11 @@ impl<'ast> Checker<'ast> {
                                     arguments_checked,
                                 )
                                 .into()),
+                                Type::Boolean => Ok(BooleanExpression::FunctionCall(
+                                    f.id.to_string(),
+                                    arguments_checked,
+                                )
+                                .into()),
                                 Type::FieldElementArray(size) => {
                                     Ok(FieldElementArrayExpression::FunctionCall(
                                         size,
@@ -714,7 +771,6 @@ impl<'ast> Checker<'ast> {
                                     )
                                     .into())
                                 }
-                                _ => unimplemented!(),
                             },
                             n => Err(Error {
                                 pos: Some(pos),
@@ -782,6 +838,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::Eq(box e1, box e2).into())
                     }
+                    (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
+                        Ok(BooleanExpression::BoolEq(box e1, box e2).into())
+                    }
+                    (
+                        TypedExpression::FieldElementArray(e1),
+                        TypedExpression::FieldElementArray(e2),
+                    ) if e1.size() == e2.size() => {
+                        Ok(BooleanExpression::ArrayEq(box e1, box e2).into())
+                    }
                     (e1, e2) => Err(Error {
                         pos: Some(pos),
                         message: format!(
@@ -940,6 +1005,96 @@ impl<'ast> Checker<'ast> {
                     }),
                 }
             }
+            Expression::FromBoolean(box e) => {
+                let e_checked = self.check_expression(e)?;
+                match e_checked {
+                    TypedExpression::Boolean(e) => {
+                        Ok(FieldElementExpression::FromBoolean(box e).into())
+                    }
+                    e => Err(Error {
+                        pos: Some(pos),
+
+                        message: format!("cannot convert {} to field", e.get_type()),
+                    }),
+                }
+            }
+            Expression::FromField(box e) => {
+                let e_checked = self.check_expression(e)?;
+                match e_checked {
+                    TypedExpression::FieldElement(e) => {
+                        Ok(BooleanExpression::FromField(box e).into())
+                    }
+                    e => Err(Error {
+                        pos: Some(pos),
+
+                        message: format!("cannot convert {} to bool", e.get_type()),
+                    }),
+                }
+            }
+            Expression::Concat(box e1, box e2) => {
+                let e1_checked = self.check_expression(e1)?;
+                let e2_checked = self.check_expression(e2)?;
+
+                match (e1_checked, e2_checked) {
+                    (
+                        TypedExpression::FieldElementArray(e1),
+                        TypedExpression::FieldElementArray(e2),
+                    ) => Ok(FieldElementArrayExpression::Concat(box e1, box e2).into()),
+                    (t1, t2) => Err(Error {
+                        pos: Some(pos),
+
+                        message: format!(
+                            "Expected only field element arrays, found {}, {}",
+                            t1.get_type(),
+                            t2.get_type()
+                        ),
+                    }),
+                }
+            }
+            Expression::Slice(box array, box from, box to) => {
+                let array = self.check_expression(array)?;
+                let from = self.check_expression(from)?;
+                let to = self.check_expression(to)?;
+
+                // the size of the slice is part of its type, so its bounds must be known
+                match (array, from, to) {
+                    (
+                        TypedExpression::FieldElementArray(a),
+                        TypedExpression::FieldElement(FieldElementExpression::Number(from)),
+                        TypedExpression::FieldElement(FieldElementExpression::Number(to)),
+                    ) => match from <= to && to <= T::from(a.size()) {
+                        // the bounds are at most the size of the array, so they fit in a usize
+                        true => Ok(FieldElementArrayExpression::Slice(
+                            (to.clone() - from.clone())
+                                .to_dec_string()
+                                .parse::<usize>()
+                                .unwrap(),
+                            box a,
+                            box FieldElementExpression::Number(from),
+                            box FieldElementExpression::Number(to),
+                        )
+                        .into()),
+                        false => Err(Error {
+                            pos: Some(pos),
+                            message: format!(
+                                "Cannot slice {}..{} of an array of size {}",
+                                from,
+                                to,
+                                a.size()
+                            ),
+                        }),
+                    },
+                    (a, from, to) => Err(Error {
+                        pos: Some(pos),
+                        message: format!(
+                            "Cannot slice {}..{} of expression of type {}, slices need constant bounds",
+                            from,
+                            to,
+                            a.get_type()
+                        ),
+                    }),
+                }
+            }
         }
     }
 
@@ -975,9 +1130,9 @@ impl<'ast> Checker<'ast> {
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
     // use absy::parameter::Parameter;
-    // use zokrates_field::field::FieldPrime;
+    use zokrates_field::field::FieldPrime;
 
     // pub fn new_with_args(
     //     scope: HashSet<ScopedVariable>,
@@ -1816,4 +1971,251 @@ mod tests {
     //         );
     //     }
     // }
+
+    mod cast {
+        use super::*;
+
+        #[test]
+        fn field_from_boolean() {
+            // field(1 == 1)
+            let e: ExpressionNode<FieldPrime> = Expression::FromBoolean(
+                box Expression::Eq(
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                )
+                .into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e),
+                Ok(FieldElementExpression::FromBoolean(
+                    box BooleanExpression::Eq(
+                        box FieldElementExpression::Number(FieldPrime::from(1)),
+                        box FieldElementExpression::Number(FieldPrime::from(1)),
+                    ),
+                )
+                .into())
+            );
+        }
+
+        #[test]
+        fn boolean_from_field() {
+            // bool(1)
+            let e: ExpressionNode<FieldPrime> =
+                Expression::FromField(box Expression::Number(FieldPrime::from(1)).into()).into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e),
+                Ok(BooleanExpression::FromField(
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                )
+                .into())
+            );
+        }
+
+        #[test]
+        fn boolean_from_boolean() {
+            // bool(1 == 1) should fail
+            let e: ExpressionNode<FieldPrime> = Expression::FromField(
+                box Expression::Eq(
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                )
+                .into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e).unwrap_err().message,
+                "cannot convert bool to bool"
+            );
+        }
+    }
+
+    mod concat {
+        use super::*;
+
+        #[test]
+        fn arrays() {
+            // [1, 2] ++ [3]
+            let e: ExpressionNode<FieldPrime> = Expression::Concat(
+                box Expression::InlineArray(vec![
+                    Expression::Number(FieldPrime::from(1)).into(),
+                    Expression::Number(FieldPrime::from(2)).into(),
+                ])
+                .into(),
+                box Expression::InlineArray(vec![Expression::Number(FieldPrime::from(3)).into()])
+                    .into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            let checked = checker.check_expression(e).unwrap();
+
+            assert_eq!(checked.get_type(), Type::FieldElementArray(3));
+            assert_eq!(
+                checked,
+                FieldElementArrayExpression::Concat(
+                    box FieldElementArrayExpression::Value(
+                        2,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                        ],
+                    ),
+                    box FieldElementArrayExpression::Value(
+                        1,
+                        vec![FieldElementExpression::Number(FieldPrime::from(3))],
+                    ),
+                )
+                .into()
+            );
+        }
+
+        #[test]
+        fn field_element() {
+            // [1] ++ 2 should fail
+            let e: ExpressionNode<FieldPrime> = Expression::Concat(
+                box Expression::InlineArray(vec![Expression::Number(FieldPrime::from(1)).into()])
+                    .into(),
+                box Expression::Number(FieldPrime::from(2)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e).unwrap_err().message,
+                "Expected only field element arrays, found field[1], field"
+            );
+        }
+    }
+
+    mod slice {
+        use super::*;
+
+        fn array() -> ExpressionNode<'static, FieldPrime> {
+            // [1, 2, 3]
+            Expression::InlineArray(vec![
+                Expression::Number(FieldPrime::from(1)).into(),
+                Expression::Number(FieldPrime::from(2)).into(),
+                Expression::Number(FieldPrime::from(3)).into(),
+            ])
+            .into()
+        }
+
+        #[test]
+        fn in_range() {
+            // [1, 2, 3][1..3]
+            let e: ExpressionNode<FieldPrime> = Expression::Slice(
+                box array(),
+                box Expression::Number(FieldPrime::from(1)).into(),
+                box Expression::Number(FieldPrime::from(3)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e),
+                Ok(FieldElementArrayExpression::Slice(
+                    2,
+                    box FieldElementArrayExpression::Value(
+                        3,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                            FieldElementExpression::Number(FieldPrime::from(3)),
+                        ],
+                    ),
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                    box FieldElementExpression::Number(FieldPrime::from(3)),
+                )
+                .into())
+            );
+        }
+
+        #[test]
+        fn out_of_range() {
+            // [1, 2, 3][1..4] should fail
+            let e: ExpressionNode<FieldPrime> = Expression::Slice(
+                box array(),
+                box Expression::Number(FieldPrime::from(1)).into(),
+                box Expression::Number(FieldPrime::from(4)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e).unwrap_err().message,
+                "Cannot slice 1..4 of an array of size 3"
+            );
+        }
+    }
+
+    mod max_min {
+        use super::*;
+
+        #[test]
+        fn max() {
+            // max(1, 2)
+            let e: ExpressionNode<FieldPrime> = Expression::Max(
+                box Expression::Number(FieldPrime::from(1)).into(),
+                box Expression::Number(FieldPrime::from(2)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e),
+                Ok(FieldElementExpression::Max(
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                )
+                .into())
+            );
+        }
+
+        #[test]
+        fn min() {
+            // min(1, 2)
+            let e: ExpressionNode<FieldPrime> = Expression::Min(
+                box Expression::Number(FieldPrime::from(1)).into(),
+                box Expression::Number(FieldPrime::from(2)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e),
+                Ok(FieldElementExpression::Min(
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                )
+                .into())
+            );
+        }
+
+        #[test]
+        fn boolean() {
+            // max(1 == 1, 2) should fail
+            let e: ExpressionNode<FieldPrime> = Expression::Max(
+                box Expression::Eq(
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                    box Expression::Number(FieldPrime::from(1)).into(),
+                )
+                .into(),
+                box Expression::Number(FieldPrime::from(2)).into(),
+            )
+            .into();
+
+            let mut checker = Checker::new();
+            assert_eq!(
+                checker.check_expression(e).unwrap_err().message,
+                "Expected only field elements, found bool, field"
+            );
+        }
+    }
 }
@@ -0,0 +1,200 @@
+//! Module containing a lint for arrays indexed beyond their size in loops
+//!
+//! Before unrolling, the range of a loop variable is given by the constant bounds of its loop. An array indexed by the
+//! loop variable in the body of the loop is indexed out of its bounds once the loop is unrolled if this range exceeds
+//! its size, which is reported here with the loop rather than later with a single unrolled index
+
+use crate::typed_absy::folder::*;
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use crate::types::Type;
+use std::collections::HashMap;
+use std::fmt;
+use zokrates_field::field::Field;
+
+pub struct ArrayBoundsChecker<'ast, T: Field> {
+    // the ranges of the variables of the enclosing loops
+    ranges: HashMap<Identifier<'ast>, (T, T)>,
+    warnings: Vec<String>,
+}
+
+impl<'ast, T: Field> ArrayBoundsChecker<'ast, T> {
+    fn new() -> Self {
+        ArrayBoundsChecker {
+            ranges: HashMap::new(),
+            warnings: vec![],
+        }
+    }
+
+    // records a warning if `index` is a loop variable ranging beyond the size of `array`
+    fn check(
+        &mut self,
+        array: &dyn fmt::Display,
+        size: usize,
+        index: &FieldElementExpression<'ast, T>,
+    ) {
+        let id = match index {
+            FieldElementExpression::Identifier(id) => id,
+            _ => return,
+        };
+
+        match self.ranges.get(id) {
+            Some((from, to)) if from < to && *to > T::from(size) => {
+                self.warnings.push(format!(
+                    "index {} ranging over {}..{} is out of bounds of {} of size {}",
+                    id.id, from, to, array, size
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns a warning for each array indexed in a loop by the loop variable, if the constant range of the loop exceeds
+/// the size of the array
+pub fn check_static_array_bounds<'ast, T: Field>(p: &TypedProg<'ast, T>) -> Vec<String> {
+    let mut checker = ArrayBoundsChecker::new();
+    checker.fold_program(p.clone());
+    checker.warnings
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for ArrayBoundsChecker<'ast, T> {
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        match s {
+            TypedStatement::For(v, from, to, statements, span) => {
+                self.ranges.insert(v.id.clone(), (from.clone(), to.clone()));
+                let statements = statements
+                    .into_iter()
+                    .flat_map(|s| self.fold_statement(s))
+                    .collect();
+                self.ranges.remove(&v.id);
+                vec![TypedStatement::For(v, from, to, statements, span)]
+            }
+            s => fold_statement(self, s),
+        }
+    }
+
+    fn fold_assignee(&mut self, a: TypedAssignee<'ast, T>) -> TypedAssignee<'ast, T> {
+        match a {
+            TypedAssignee::ArrayElement(box a, box index) => {
+                if let TypedAssignee::Identifier(ref v) = a {
+                    if let Type::FieldElementArray(size) = v.get_type() {
+                        self.check(&v.id.id, size, &index);
+                    }
+                }
+
+                TypedAssignee::ArrayElement(
+                    box self.fold_assignee(a),
+                    box self.fold_field_expression(index),
+                )
+            }
+            a => a,
+        }
+    }
+
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        match e {
+            FieldElementExpression::Select(
+                box FieldElementArrayExpression::Identifier(size, ref id),
+                box ref index,
+            ) => self.check(&id.id, size, index),
+            FieldElementExpression::Select(box ref array, box ref index) => {
+                self.check(array, array.size(), index)
+            }
+            _ => {}
+        }
+
+        fold_field_expression(self, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Signature;
+    use zokrates_field::field::FieldPrime;
+
+    fn main(
+        statements: Vec<TypedStatement<'static, FieldPrime>>,
+    ) -> TypedProg<'static, FieldPrime> {
+        TypedProg {
+            functions: vec![TypedFunction {
+                id: "main",
+                arguments: vec![],
+                statements,
+                signature: Signature::new(),
+            }],
+            imports: vec![],
+            imported_functions: vec![],
+        }
+    }
+
+    // for field i in 0..`to` do
+    //     field x = a[i]
+    // endfor
+    fn read_in_loop(to: usize) -> TypedProg<'static, FieldPrime> {
+        main(vec![
+            TypedStatement::Declaration(Variable::field_array("a".into(), 3), None),
+            TypedStatement::For(
+                Variable::field_element("i".into()),
+                FieldPrime::from(0),
+                FieldPrime::from(to),
+                vec![TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("x".into())),
+                    FieldElementExpression::Select(
+                        box FieldElementArrayExpression::Identifier(3, "a".into()),
+                        box FieldElementExpression::Identifier("i".into()),
+                    )
+                    .into(),
+                    None,
+                )],
+                None,
+            ),
+        ])
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        assert_eq!(
+            check_static_array_bounds(&read_in_loop(5)),
+            vec![String::from("index i ranging over 0..5 is out of bounds of a of size 3")]
+        );
+    }
+
+    #[test]
+    fn in_bounds() {
+        assert_eq!(check_static_array_bounds(&read_in_loop(3)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn assignee() {
+        // for field i in 0..4 do
+        //     a[i] = 1
+        // endfor
+        let p = main(vec![
+            TypedStatement::Declaration(Variable::field_array("a".into(), 3), None),
+            TypedStatement::For(
+                Variable::field_element("i".into()),
+                FieldPrime::from(0),
+                FieldPrime::from(4),
+                vec![TypedStatement::Definition(
+                    TypedAssignee::ArrayElement(
+                        box TypedAssignee::Identifier(Variable::field_array("a".into(), 3)),
+                        box FieldElementExpression::Identifier("i".into()),
+                    ),
+                    FieldElementExpression::Number(FieldPrime::from(1)).into(),
+                    None,
+                )],
+                None,
+            ),
+        ]);
+
+        assert_eq!(
+            check_static_array_bounds(&p),
+            vec![String::from("index i ranging over 0..4 is out of bounds of a of size 3")]
+        );
+    }
+}
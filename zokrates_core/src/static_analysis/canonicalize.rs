@@ -0,0 +1,164 @@
+//! Module containing the canonicalization of commutative operations
+//!
+//! The operands of commutative operations are ordered structurally, so that `a + b` and `b + a` are the same tree and
+//! programs compile to the same output regardless of how their expressions were written
+
+use crate::typed_absy::folder::*;
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use zokrates_field::field::Field;
+
+pub struct Canonicalizer {}
+
+impl Canonicalizer {
+    fn new() -> Self {
+        Canonicalizer {}
+    }
+
+    pub fn canonicalize<T: Field>(p: TypedProg<T>) -> TypedProg<T> {
+        Canonicalizer::new().fold_program(p)
+    }
+}
+
+// orders two operands structurally, keeping them in place if they are equal
+fn ordered<E: Ord>(e1: E, e2: E) -> (E, E) {
+    match e1 <= e2 {
+        true => (e1, e2),
+        false => (e2, e1),
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for Canonicalizer {
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        // operands are canonicalized first, so that their representation is canonical too
+        match fold_field_expression(self, e) {
+            FieldElementExpression::Add(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                FieldElementExpression::Add(box e1, box e2)
+            }
+            FieldElementExpression::Mult(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                FieldElementExpression::Mult(box e1, box e2)
+            }
+            FieldElementExpression::Max(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                FieldElementExpression::Max(box e1, box e2)
+            }
+            FieldElementExpression::Min(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                FieldElementExpression::Min(box e1, box e2)
+            }
+            e => e,
+        }
+    }
+
+    fn fold_boolean_expression(
+        &mut self,
+        e: BooleanExpression<'ast, T>,
+    ) -> BooleanExpression<'ast, T> {
+        match fold_boolean_expression(self, e) {
+            BooleanExpression::And(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                BooleanExpression::And(box e1, box e2)
+            }
+            BooleanExpression::Or(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                BooleanExpression::Or(box e1, box e2)
+            }
+            BooleanExpression::Eq(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                BooleanExpression::Eq(box e1, box e2)
+            }
+            BooleanExpression::BoolEq(box e1, box e2) => {
+                let (e1, e2) = ordered(e1, e2);
+                BooleanExpression::BoolEq(box e1, box e2)
+            }
+            e => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::field::FieldPrime;
+
+    #[test]
+    fn add() {
+        // b + a -> a + b
+        let e: FieldElementExpression<FieldPrime> = FieldElementExpression::Add(
+            box FieldElementExpression::Identifier("b".into()),
+            box FieldElementExpression::Identifier("a".into()),
+        );
+
+        let expected = FieldElementExpression::Add(
+            box FieldElementExpression::Identifier("a".into()),
+            box FieldElementExpression::Identifier("b".into()),
+        );
+
+        assert_eq!(
+            Canonicalizer::new().fold_field_expression(e),
+            Canonicalizer::new().fold_field_expression(expected.clone())
+        );
+        assert_eq!(Canonicalizer::new().fold_field_expression(expected.clone()), expected);
+    }
+
+    #[test]
+    fn nested() {
+        // (c * b) == d -> d == (b * c), the order depends on the operands once they are canonical
+        let e: BooleanExpression<FieldPrime> = BooleanExpression::Eq(
+            box FieldElementExpression::Mult(
+                box FieldElementExpression::Identifier("c".into()),
+                box FieldElementExpression::Identifier("b".into()),
+            ),
+            box FieldElementExpression::Identifier("d".into()),
+        );
+
+        assert_eq!(
+            Canonicalizer::new().fold_boolean_expression(e),
+            BooleanExpression::Eq(
+                box FieldElementExpression::Identifier("d".into()),
+                box FieldElementExpression::Mult(
+                    box FieldElementExpression::Identifier("b".into()),
+                    box FieldElementExpression::Identifier("c".into()),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn same_representation() {
+        // a[0] + a[0] where the arrays only differ in their size are ordered the same way whichever comes first
+        let select = |size| {
+            FieldElementExpression::Select(
+                box FieldElementArrayExpression::Identifier(size, "a".into()),
+                box FieldElementExpression::Number(FieldPrime::from(0)),
+            )
+        };
+
+        assert_eq!(select(2).to_string(), select(3).to_string());
+
+        let expected = FieldElementExpression::Add(box select(2), box select(3));
+
+        assert_eq!(
+            Canonicalizer::new()
+                .fold_field_expression(FieldElementExpression::Add(box select(3), box select(2))),
+            expected
+        );
+        assert_eq!(Canonicalizer::new().fold_field_expression(expected.clone()), expected);
+    }
+
+    #[test]
+    fn non_commutative() {
+        // b - a is left as is
+        let e: FieldElementExpression<FieldPrime> = FieldElementExpression::Sub(
+            box FieldElementExpression::Identifier("b".into()),
+            box FieldElementExpression::Identifier("a".into()),
+        );
+
+        assert_eq!(Canonicalizer::new().fold_field_expression(e.clone()), e);
+    }
+}
@@ -5,6 +5,111 @@ use crate::types::{Signature, Type};
 use std::collections::HashSet;
 use zokrates_field::field::Field;
 
+// the array elements read at a constant index, and the arrays read otherwise, as a whole or at a symbolic index
+#[derive(Default)]
+struct ArrayReads<'ast> {
+    elements: HashSet<(Identifier<'ast>, usize)>,
+    arrays: HashSet<Identifier<'ast>>,
+}
+
+impl<'ast> ArrayReads<'ast> {
+    fn is_read(&self, array: &Identifier<'ast>, index: usize) -> bool {
+        self.arrays.contains(array) || self.elements.contains(&(array.clone(), index))
+    }
+
+    // an array defined as a whole is not read before its definition
+    fn kill(&mut self, array: &Identifier<'ast>) {
+        self.arrays.remove(array);
+        self.elements.retain(|(id, _)| id != array);
+    }
+
+    fn read<T: Field>(&mut self, s: &TypedStatement<'ast, T>) {
+        self.fold_statement(s.clone());
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for ArrayReads<'ast> {
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        match e {
+            FieldElementExpression::Select(
+                box FieldElementArrayExpression::Identifier(size, id),
+                box FieldElementExpression::Number(n),
+            ) => {
+                match constant_index(&n) {
+                    Some(index) => self.elements.insert((id.clone(), index)),
+                    None => self.arrays.insert(id.clone()),
+                };
+                FieldElementExpression::Select(
+                    box FieldElementArrayExpression::Identifier(size, id),
+                    box FieldElementExpression::Number(n),
+                )
+            }
+            e => fold_field_expression(self, e),
+        }
+    }
+
+    fn fold_field_array_expression(
+        &mut self,
+        e: FieldElementArrayExpression<'ast, T>,
+    ) -> FieldElementArrayExpression<'ast, T> {
+        if let FieldElementArrayExpression::Identifier(_, ref id) = e {
+            self.arrays.insert(id.clone());
+        }
+        fold_field_array_expression(self, e)
+    }
+}
+
+fn constant_index<T: Field>(n: &T) -> Option<usize> {
+    n.to_dec_string().parse().ok()
+}
+
+// removes the writes to array elements at a constant index which are not read before the element is written again or
+// the function returns, going through the statements backwards. Writes at a symbolic index are kept, and so are writes
+// in loops, which may not run
+fn remove_dead_stores<'ast, T: Field>(
+    statements: Vec<TypedStatement<'ast, T>>,
+) -> Vec<TypedStatement<'ast, T>> {
+    let mut live = ArrayReads::default();
+    let mut kept = vec![];
+
+    for s in statements.into_iter().rev() {
+        match s {
+            TypedStatement::Definition(
+                TypedAssignee::ArrayElement(
+                    box TypedAssignee::Identifier(ref v),
+                    box FieldElementExpression::Number(ref n),
+                ),
+                _,
+                _,
+            ) => {
+                if let Some(index) = constant_index(n) {
+                    if !live.is_read(&v.id, index) {
+                        continue;
+                    }
+                    live.elements.remove(&(v.id.clone(), index));
+                }
+            }
+            TypedStatement::Definition(TypedAssignee::Identifier(ref v), _, _)
+            | TypedStatement::Declaration(ref v, _) => live.kill(&v.id),
+            TypedStatement::MultipleDefinition(ref variables, _, _) => {
+                for v in variables {
+                    live.kill(&v.id);
+                }
+            }
+            _ => {}
+        }
+
+        live.read(&s);
+        kept.push(s);
+    }
+
+    kept.reverse();
+    kept
+}
+
 pub struct DeadCode {
     called: HashSet<String>,
 }
@@ -19,6 +124,14 @@ impl DeadCode {
     pub fn clean<T: Field>(p: TypedProg<T>) -> TypedProg<T> {
         DeadCode::new().fold_program(p)
     }
+
+    /// Returns the slugs of the functions called from `f`
+    #[cfg(feature = "parallel")]
+    pub fn calls<T: Field>(f: TypedFunction<T>) -> HashSet<String> {
+        let mut dead_code = DeadCode::new();
+        dead_code.fold_function(f);
+        dead_code.called
+    }
 }
 
 impl<'ast, T: Field> Folder<'ast, T> for DeadCode {
@@ -36,10 +149,15 @@ impl<'ast, T: Field> Folder<'ast, T> for DeadCode {
         }
     }
 
+    fn fold_function(&mut self, f: TypedFunction<'ast, T>) -> TypedFunction<'ast, T> {
+        let statements = remove_dead_stores(f.statements);
+        fold_function(self, TypedFunction { statements, ..f })
+    }
+
     // add extra statements before the modified statement
     fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
         match s {
-            TypedStatement::MultipleDefinition(variables, elist) => match elist {
+            TypedStatement::MultipleDefinition(variables, elist, span) => match elist {
                 TypedExpressionList::FunctionCall(id, exps, types) => {
                     let exps: Vec<_> = exps.into_iter().map(|e| self.fold_expression(e)).collect();
 
@@ -52,6 +170,7 @@ impl<'ast, T: Field> Folder<'ast, T> for DeadCode {
                     vec![TypedStatement::MultipleDefinition(
                         variables,
                         TypedExpressionList::FunctionCall(id, exps, types),
+                        span,
                     )]
                 }
             },
@@ -79,6 +198,26 @@ impl<'ast, T: Field> Folder<'ast, T> for DeadCode {
         }
     }
 
+    fn fold_boolean_expression(
+        &mut self,
+        e: BooleanExpression<'ast, T>,
+    ) -> BooleanExpression<'ast, T> {
+        match e {
+            BooleanExpression::FunctionCall(id, exps) => {
+                let exps: Vec<_> = exps.into_iter().map(|e| self.fold_expression(e)).collect();
+
+                let signature = Signature::new()
+                    .inputs(exps.iter().map(|e| e.get_type()).collect())
+                    .outputs(vec![Type::Boolean]);
+
+                self.called
+                    .insert(format!("{}_{}", id, signature.to_slug()));
+                BooleanExpression::FunctionCall(id, exps)
+            }
+            e => fold_boolean_expression(self, e),
+        }
+    }
+
     fn fold_field_array_expression(
         &mut self,
         e: FieldElementArrayExpression<'ast, T>,
@@ -99,3 +238,113 @@ impl<'ast, T: Field> Folder<'ast, T> for DeadCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::field::FieldPrime;
+
+    fn main(statements: Vec<TypedStatement<FieldPrime>>) -> TypedFunction<FieldPrime> {
+        TypedFunction {
+            id: "main",
+            arguments: vec![],
+            statements,
+            signature: Signature::new().outputs(vec![Type::FieldElement]),
+        }
+    }
+
+    fn write<'ast>(
+        index: FieldElementExpression<'ast, FieldPrime>,
+        value: usize,
+    ) -> TypedStatement<'ast, FieldPrime> {
+        TypedStatement::Definition(
+            TypedAssignee::ArrayElement(
+                box TypedAssignee::Identifier(Variable::field_array("a".into(), 3)),
+                box index,
+            ),
+            FieldElementExpression::Number(FieldPrime::from(value)).into(),
+            None,
+        )
+    }
+
+    fn number<'ast>(n: usize) -> FieldElementExpression<'ast, FieldPrime> {
+        FieldElementExpression::Number(FieldPrime::from(n))
+    }
+
+    fn read<'ast>(index: usize) -> FieldElementExpression<'ast, FieldPrime> {
+        FieldElementExpression::Select(
+            box FieldElementArrayExpression::Identifier(3, "a".into()),
+            box number(index),
+        )
+    }
+
+    fn define_a<'ast>() -> TypedStatement<'ast, FieldPrime> {
+        TypedStatement::Definition(
+            TypedAssignee::Identifier(Variable::field_array("a".into(), 3)),
+            FieldElementArrayExpression::Value(3, vec![number(1), number(2), number(3)]).into(),
+            None,
+        )
+    }
+
+    #[test]
+    fn dead_store() {
+        // field[3] a = [1, 2, 3]
+        // a[2] = 5
+        // a[1] = 4
+        // a[1] = 6
+        // return a[1]
+        let f = main(vec![
+            define_a(),
+            write(number(2), 5),
+            write(number(1), 4),
+            write(number(1), 6),
+            TypedStatement::Return(vec![read(1).into()], None),
+        ]);
+
+        // a[2] is never read, and a[1] is written again before it is read
+        assert_eq!(
+            DeadCode::new().fold_function(f).statements,
+            vec![
+                define_a(),
+                write(number(1), 6),
+                TypedStatement::Return(vec![read(1).into()], None),
+            ]
+        );
+    }
+
+    #[test]
+    fn live_stores() {
+        // field[3] a = [1, 2, 3]
+        // a[0] = 4
+        // a[x] = 5
+        // return a[2] + a[0]
+        let f = main(vec![
+            define_a(),
+            write(number(0), 4),
+            write(FieldElementExpression::Identifier("x".into()), 5),
+            TypedStatement::Return(
+                vec![FieldElementExpression::Add(box read(2), box read(0)).into()],
+                None,
+            ),
+        ]);
+
+        assert_eq!(DeadCode::new().fold_function(f.clone()).statements, f.statements);
+
+        // field[3] a = [1, 2, 3]
+        // a[2] = 5
+        // return a
+        let f = TypedFunction {
+            signature: Signature::new().outputs(vec![Type::FieldElementArray(3)]),
+            ..main(vec![
+                define_a(),
+                write(number(2), 5),
+                TypedStatement::Return(
+                    vec![FieldElementArrayExpression::Identifier(3, "a".into()).into()],
+                    None,
+                ),
+            ])
+        };
+
+        assert_eq!(DeadCode::new().fold_function(f.clone()).statements, f.statements);
+    }
+}
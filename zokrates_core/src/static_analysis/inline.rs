@@ -10,6 +10,9 @@ pub struct Inliner<'ast, T: Field> {
     statements_buffer: Vec<TypedStatement<'ast, T>>,
     context: Vec<(&'ast str, Signature, usize)>,
     call_count: HashMap<String, usize>,
+    // position of the statement being inlined into, which the statements binding the inputs of a
+    // call are given
+    span: Option<Span>,
 }
 
 impl<'ast, T: Field> Inliner<'ast, T> {
@@ -19,6 +22,7 @@ impl<'ast, T: Field> Inliner<'ast, T> {
             statements_buffer: vec![],
             context: vec![],
             call_count: HashMap::new(),
+            span: None,
         }
     }
 
@@ -56,6 +60,8 @@ impl<'ast, T: Field> Inliner<'ast, T> {
         function: TypedFunction<'ast, T>,
         expressions: Vec<TypedExpression<'ast, T>>,
     ) -> Vec<TypedExpression<'ast, T>> {
+        let span = self.span;
+
         self.call_count
             .entry(function.to_slug())
             .and_modify(|i| *i += 1)
@@ -75,6 +81,7 @@ impl<'ast, T: Field> Inliner<'ast, T> {
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(self.fold_variable(a.id.clone())),
                     e,
+                    span,
                 )
             })
             .collect();
@@ -94,11 +101,14 @@ impl<'ast, T: Field> Inliner<'ast, T> {
         // add all statements to the buffer
         self.statements_buffer.append(&mut statements);
 
+        // the statements of `function` keep their own positions, go back to the one of the call
+        self.span = span;
+
         // remove this call from the context
         self.context.pop();
 
         match ret[0].clone() {
-            TypedStatement::Return(exprs) => exprs,
+            TypedStatement::Return(exprs, _) => exprs,
             _ => panic!(""),
         }
     }
@@ -117,8 +127,11 @@ impl<'ast, T: Field> Folder<'ast, T> for Inliner<'ast, T> {
 
     // add extra statements before the modified statement
     fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        let span = s.span();
+        self.span = span;
+
         let mut statements = match s {
-            TypedStatement::MultipleDefinition(variables, elist) => {
+            TypedStatement::MultipleDefinition(variables, elist, _) => {
                 match elist {
                     TypedExpressionList::FunctionCall(id, exps, types) => {
                         let variables: Vec<_> = variables
@@ -146,13 +159,18 @@ impl<'ast, T: Field> Folder<'ast, T> for Inliner<'ast, T> {
                                     .into_iter()
                                     .zip(ret.into_iter())
                                     .map(|(v, e)| {
-                                        TypedStatement::Definition(TypedAssignee::Identifier(v), e)
+                                        TypedStatement::Definition(
+                                            TypedAssignee::Identifier(v),
+                                            e,
+                                            span,
+                                        )
                                     })
                                     .collect()
                             }
                             false => vec![TypedStatement::MultipleDefinition(
                                 variables,
                                 TypedExpressionList::FunctionCall(id, exps, types),
+                                span,
                             )],
                         }
                     }
@@ -212,6 +230,43 @@ impl<'ast, T: Field> Folder<'ast, T> for Inliner<'ast, T> {
         }
     }
 
+    // inline calls which return a boolean
+    fn fold_boolean_expression(
+        &mut self,
+        e: BooleanExpression<'ast, T>,
+    ) -> BooleanExpression<'ast, T> {
+        match e {
+            BooleanExpression::FunctionCall(id, exps) => {
+                let exps: Vec<_> = exps.into_iter().map(|e| self.fold_expression(e)).collect();
+
+                let passed_signature = Signature::new()
+                    .inputs(exps.iter().map(|e| e.get_type()).collect())
+                    .outputs(vec![Type::Boolean]);
+
+                // find the function
+                let function = self
+                    .functions
+                    .iter()
+                    .find(|f| f.id == id && f.signature == passed_signature)
+                    .cloned();
+
+                match self.should_inline(&function, &exps) {
+                    true => {
+                        let ret = self.inline_call(function.unwrap(), exps);
+                        // unwrap the result to return a boolean
+                        match ret[0].clone() {
+                            TypedExpression::Boolean(e) => e,
+                            _ => panic!(""),
+                        }
+                    }
+                    false => BooleanExpression::FunctionCall(id, exps),
+                }
+            }
+            // default
+            e => fold_boolean_expression(self, e),
+        }
+    }
+
     // inline calls which return a field element array
     fn fold_field_array_expression(
         &mut self,
@@ -267,13 +322,14 @@ mod tests {
                     Parameter::private(Variable::field_element("a".into())),
                     Parameter::private(Variable::field_array("b".into(), 3)),
                 ],
-                statements: vec![TypedStatement::Return(vec![
-                    FieldElementExpression::Select(
+                statements: vec![TypedStatement::Return(
+                    vec![FieldElementExpression::Select(
                         box FieldElementArrayExpression::Identifier(3, Identifier::from("b")),
                         box FieldElementExpression::Identifier(Identifier::from("a")),
                     )
-                    .into(),
-                ])],
+                    .into()],
+                    None,
+                )],
                 signature: Signature::new()
                     .inputs(vec![Type::FieldElement, Type::FieldElementArray(3)])
                     .outputs(vec![Type::FieldElement]),
@@ -297,13 +353,14 @@ mod tests {
                     Parameter::private(Variable::field_element("a".into())),
                     Parameter::private(Variable::field_array("b".into(), 3)),
                 ],
-                statements: vec![TypedStatement::Return(vec![
-                    FieldElementExpression::Select(
+                statements: vec![TypedStatement::Return(
+                    vec![FieldElementExpression::Select(
                         box FieldElementArrayExpression::Identifier(3, Identifier::from("b")),
                         box FieldElementExpression::Identifier(Identifier::from("a")),
                     )
-                    .into(),
-                ])],
+                    .into()],
+                    None,
+                )],
                 signature: Signature::new()
                     .inputs(vec![Type::FieldElement, Type::FieldElementArray(3)])
                     .outputs(vec![Type::FieldElement]),
@@ -319,4 +376,178 @@ mod tests {
             assert!(!i.should_inline(&Some(f), &arguments));
         }
     }
+
+    #[cfg(test)]
+    mod multiple_definition {
+        use super::*;
+        use crate::static_analysis::propagation::Propagator;
+
+        #[test]
+        fn fold_constant_call() {
+            // def split(x):
+            //   return x - 2, 2
+            //
+            // def main():
+            //   a, b = split(12)
+            //   return a * b
+            //
+            // inlining then propagating defines a and b as constants
+            let split: TypedFunction<FieldPrime> = TypedFunction {
+                id: "split",
+                arguments: vec![Parameter::private(Variable::field_element("x".into()))],
+                statements: vec![TypedStatement::Return(
+                    vec![
+                        FieldElementExpression::Sub(
+                            box FieldElementExpression::Identifier("x".into()),
+                            box FieldElementExpression::Number(FieldPrime::from(2)),
+                        )
+                        .into(),
+                        FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                    ],
+                    None,
+                )],
+                signature: Signature::new()
+                    .inputs(vec![Type::FieldElement])
+                    .outputs(vec![Type::FieldElement, Type::FieldElement]),
+            };
+
+            let main: TypedFunction<FieldPrime> = TypedFunction {
+                id: "main",
+                arguments: vec![],
+                statements: vec![
+                    TypedStatement::MultipleDefinition(
+                        vec![
+                            Variable::field_element("a".into()),
+                            Variable::field_element("b".into()),
+                        ],
+                        TypedExpressionList::FunctionCall(
+                            String::from("split"),
+                            vec![FieldElementExpression::Number(FieldPrime::from(12)).into()],
+                            vec![Type::FieldElement, Type::FieldElement],
+                        ),
+                        None,
+                    ),
+                    TypedStatement::Return(
+                        vec![FieldElementExpression::Mult(
+                            box FieldElementExpression::Identifier("a".into()),
+                            box FieldElementExpression::Identifier("b".into()),
+                        )
+                        .into()],
+                        None,
+                    ),
+                ],
+                signature: Signature::new().outputs(vec![Type::FieldElement]),
+            };
+
+            let p = TypedProg {
+                functions: vec![main, split],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            let p = Propagator::propagate(Inliner::inline(p));
+
+            assert_eq!(
+                p.functions[0].statements,
+                vec![TypedStatement::Return(
+                    vec![FieldElementExpression::Number(FieldPrime::from(20)).into()],
+                    None
+                )]
+            );
+        }
+
+        #[test]
+        fn span() {
+            use crate::parser::Position;
+
+            // def split(x):
+            //   y = x - 2 at 2:3
+            //   return y, 2
+            //
+            // def main():
+            //   a, b = split(12) at 5:3
+            //   return a * b
+            //
+            // the inlined statements of split keep their position, the bindings of the call take
+            // the one of the call
+            let definition_span = (Position { line: 2, col: 3 }, Position { line: 2, col: 12 });
+            let call_span = (Position { line: 5, col: 3 }, Position { line: 5, col: 19 });
+
+            let split: TypedFunction<FieldPrime> = TypedFunction {
+                id: "split",
+                arguments: vec![Parameter::private(Variable::field_element("x".into()))],
+                statements: vec![
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("y".into())),
+                        FieldElementExpression::Sub(
+                            box FieldElementExpression::Identifier("x".into()),
+                            box FieldElementExpression::Number(FieldPrime::from(2)),
+                        )
+                        .into(),
+                        Some(definition_span),
+                    ),
+                    TypedStatement::Return(
+                        vec![
+                            FieldElementExpression::Identifier("y".into()).into(),
+                            FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                        ],
+                        None,
+                    ),
+                ],
+                signature: Signature::new()
+                    .inputs(vec![Type::FieldElement])
+                    .outputs(vec![Type::FieldElement, Type::FieldElement]),
+            };
+
+            let main: TypedFunction<FieldPrime> = TypedFunction {
+                id: "main",
+                arguments: vec![],
+                statements: vec![
+                    TypedStatement::MultipleDefinition(
+                        vec![
+                            Variable::field_element("a".into()),
+                            Variable::field_element("b".into()),
+                        ],
+                        TypedExpressionList::FunctionCall(
+                            String::from("split"),
+                            vec![FieldElementExpression::Number(FieldPrime::from(12)).into()],
+                            vec![Type::FieldElement, Type::FieldElement],
+                        ),
+                        Some(call_span),
+                    ),
+                    TypedStatement::Return(
+                        vec![FieldElementExpression::Mult(
+                            box FieldElementExpression::Identifier("a".into()),
+                            box FieldElementExpression::Identifier("b".into()),
+                        )
+                        .into()],
+                        None,
+                    ),
+                ],
+                signature: Signature::new().outputs(vec![Type::FieldElement]),
+            };
+
+            let p = Inliner::inline(TypedProg {
+                functions: vec![main, split],
+                imports: vec![],
+                imported_functions: vec![],
+            });
+
+            // x = 12, y = x - 2, a = y, b = 2, return a * b
+            assert_eq!(
+                p.functions[0]
+                    .statements
+                    .iter()
+                    .map(|s| s.span())
+                    .collect::<Vec<_>>(),
+                vec![
+                    Some(call_span),
+                    Some(definition_span),
+                    Some(call_span),
+                    Some(call_span),
+                    None
+                ]
+            );
+        }
+    }
 }
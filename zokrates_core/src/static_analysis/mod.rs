@@ -4,45 +4,87 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
 
+mod array_bounds;
+mod canonicalize;
 mod dead_code;
 mod flat_propagation;
 mod inline;
 mod power_check;
 mod propagation;
 mod unroll;
+mod unused_parameters;
 
+pub use self::array_bounds::check_static_array_bounds;
+use self::canonicalize::Canonicalizer;
 use self::dead_code::DeadCode;
 use self::inline::Inliner;
 use self::power_check::PowerChecker;
-use self::propagation::Propagator;
+pub use self::propagation::{
+    rename_and_merge, Constants, FoldingRules, PropagationError, PropagationOptions, Propagator,
+    DEFAULT_MAX_DEPTH, DEFAULT_MAX_MATERIALIZED_ARRAY,
+};
 use self::unroll::Unroller;
+use self::unused_parameters::{UnusedParameter, UnusedParameters};
 use crate::flat_absy::FlatProg;
 use crate::typed_absy::TypedProg;
+use std::fmt;
 use zokrates_field::field::Field;
 
-pub trait Analyse {
-    fn analyse(self) -> Self;
+/// A problem found by static analysis which does not prevent compiling the program
+#[derive(Debug, PartialEq)]
+pub enum Warning {
+    /// An array indexed beyond its size in a loop, see `check_static_array_bounds`
+    ArrayBounds(String),
+    /// A function parameter which is never read
+    UnusedParameter(UnusedParameter),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::ArrayBounds(ref message) => write!(f, "{}", message),
+            Warning::UnusedParameter(ref parameter) => write!(f, "{}", parameter),
+        }
+    }
+}
+
+pub trait Analyse: Sized {
+    /// Returns the analysed program along with the warnings found on the way
+    fn analyse(self) -> Result<(Self, Vec<Warning>), PropagationError>;
 }
 
 impl<'ast, T: Field> Analyse for TypedProg<'ast, T> {
-    fn analyse(self) -> Self {
+    fn analyse(self) -> Result<(Self, Vec<Warning>), PropagationError> {
         let r = PowerChecker::check(self);
+        // report arrays indexed beyond their size in loops, while the loops are still there
+        let mut warnings: Vec<_> = check_static_array_bounds(&r)
+            .into_iter()
+            .map(Warning::ArrayBounds)
+            .collect();
+        // report unused parameters before inlining removes the functions they belong to
+        warnings.extend(
+            UnusedParameters::detect(&r)
+                .into_iter()
+                .map(Warning::UnusedParameter),
+        );
         // unroll
         let r = Unroller::unroll(r);
         //propagate a first time for constants to reach function calls
-        let r = Propagator::propagate(r);
+        let r = r.try_propagate()?;
         // apply inlining strategy
         let r = Inliner::inline(r);
         // Propagate again
-        let r = Propagator::propagate(r);
+        let r = r.try_propagate()?;
+        // order the operands of commutative operations
+        let r = Canonicalizer::canonicalize(r);
         // remove unused functions
         let r = DeadCode::clean(r);
-        r
+        Ok((r, warnings))
     }
 }
 
 impl<T: Field> Analyse for FlatProg<T> {
-    fn analyse(self) -> Self {
-        self.propagate()
+    fn analyse(self) -> Result<(Self, Vec<Warning>), PropagationError> {
+        Ok((self.propagate(), vec![]))
     }
 }
@@ -48,18 +48,20 @@ impl<'ast> Unroller<'ast> {
 impl<'ast, T: Field> Folder<'ast, T> for Unroller<'ast> {
     fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
         match s {
-            TypedStatement::Declaration(_) => vec![],
-            TypedStatement::Definition(TypedAssignee::Identifier(variable), expr) => {
+            TypedStatement::Declaration(_, _) => vec![],
+            TypedStatement::Definition(TypedAssignee::Identifier(variable), expr, span) => {
                 let expr = self.fold_expression(expr);
 
                 vec![TypedStatement::Definition(
                     TypedAssignee::Identifier(self.issue_next_ssa_variable(variable)),
                     expr,
+                    span,
                 )]
             }
             TypedStatement::Definition(
                 TypedAssignee::ArrayElement(array @ box TypedAssignee::Identifier(..), box index),
                 expr,
+                span,
             ) => {
                 let expr = self.fold_expression(expr);
                 let index = self.fold_field_expression(index);
@@ -112,18 +114,19 @@ impl<'ast, T: Field> Folder<'ast, T> for Unroller<'ast> {
                 vec![TypedStatement::Definition(
                     TypedAssignee::Identifier(new_variable),
                     new_array.into(),
+                    span,
                 )]
             }
-            TypedStatement::MultipleDefinition(variables, exprs) => {
+            TypedStatement::MultipleDefinition(variables, exprs, span) => {
                 let exprs = self.fold_expression_list(exprs);
                 let variables = variables
                     .into_iter()
                     .map(|v| self.issue_next_ssa_variable(v))
                     .collect();
 
-                vec![TypedStatement::MultipleDefinition(variables, exprs)]
+                vec![TypedStatement::MultipleDefinition(variables, exprs, span)]
             }
-            TypedStatement::For(v, from, to, stats) => {
+            TypedStatement::For(v, from, to, stats, span) => {
                 let mut values: Vec<T> = vec![];
                 let mut current = from;
                 while current < to {
@@ -136,10 +139,11 @@ impl<'ast, T: Field> Folder<'ast, T> for Unroller<'ast> {
                     .map(|index| {
                         vec![
                             vec![
-                                TypedStatement::Declaration(v.clone()),
+                                TypedStatement::Declaration(v.clone(), span),
                                 TypedStatement::Definition(
                                     TypedAssignee::Identifier(v.clone()),
                                     FieldElementExpression::Number(index).into(),
+                                    span,
                                 ),
                             ],
                             stats.clone(),
@@ -201,12 +205,14 @@ mod tests {
                 FieldPrime::from(2),
                 FieldPrime::from(5),
                 vec![
-                    TypedStatement::Declaration(Variable::field_element("foo".into())),
+                    TypedStatement::Declaration(Variable::field_element("foo".into()), None),
                     TypedStatement::Definition(
                         TypedAssignee::Identifier(Variable::field_element("foo".into())),
                         FieldElementExpression::Identifier("i".into()).into(),
+                        None,
                     ),
                 ],
+                None,
             );
 
             let expected = vec![
@@ -215,36 +221,42 @@ mod tests {
                         Identifier::from("i").version(0),
                     )),
                     FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("foo").version(0),
                     )),
                     FieldElementExpression::Identifier(Identifier::from("i").version(0)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("i").version(1),
                     )),
                     FieldElementExpression::Number(FieldPrime::from(3)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("foo").version(1),
                     )),
                     FieldElementExpression::Identifier(Identifier::from("i").version(1)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("i").version(2),
                     )),
                     FieldElementExpression::Number(FieldPrime::from(4)).into(),
+                    None,
                 ),
                 TypedStatement::Definition(
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("foo").version(2),
                     )),
                     FieldElementExpression::Identifier(Identifier::from("i").version(2)).into(),
+                    None,
                 ),
             ];
 
@@ -253,6 +265,45 @@ mod tests {
             assert_eq!(u.fold_statement(s), expected);
         }
 
+        #[test]
+        fn for_loop_span() {
+            use crate::parser::Position;
+
+            // for field i in 2..4 at 1:1
+            //		field foo = i at 2:5
+            // the definitions of i take the position of the loop, and the statements of the body
+            // keep theirs
+            let for_span = (Position { line: 1, col: 1 }, Position { line: 3, col: 7 });
+            let foo_span = (Position { line: 2, col: 5 }, Position { line: 2, col: 18 });
+
+            let s: TypedStatement<FieldPrime> = TypedStatement::For(
+                Variable::field_element("i".into()),
+                FieldPrime::from(2),
+                FieldPrime::from(4),
+                vec![TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("foo".into())),
+                    FieldElementExpression::Identifier("i".into()).into(),
+                    Some(foo_span),
+                )],
+                Some(for_span),
+            );
+
+            let mut u = Unroller::new();
+
+            assert_eq!(
+                u.fold_statement(s)
+                    .iter()
+                    .map(|s| s.span())
+                    .collect::<Vec<_>>(),
+                vec![
+                    Some(for_span),
+                    Some(foo_span),
+                    Some(for_span),
+                    Some(foo_span)
+                ]
+            );
+        }
+
         #[test]
         fn definition() {
             // field a
@@ -268,12 +319,13 @@ mod tests {
             let mut u = Unroller::new();
 
             let s: TypedStatement<FieldPrime> =
-                TypedStatement::Declaration(Variable::field_element("a".into()));
+                TypedStatement::Declaration(Variable::field_element("a".into()), None);
             assert_eq!(u.fold_statement(s), vec![]);
 
             let s = TypedStatement::Definition(
                 TypedAssignee::Identifier(Variable::field_element("a".into())),
                 FieldElementExpression::Number(FieldPrime::from(5)).into(),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -281,13 +333,15 @@ mod tests {
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("a").version(0)
                     )),
-                    FieldElementExpression::Number(FieldPrime::from(5)).into()
+                    FieldElementExpression::Number(FieldPrime::from(5)).into(),
+                    None
                 )]
             );
 
             let s = TypedStatement::Definition(
                 TypedAssignee::Identifier(Variable::field_element("a".into())),
                 FieldElementExpression::Number(FieldPrime::from(6)).into(),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -295,7 +349,8 @@ mod tests {
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("a").version(1)
                     )),
-                    FieldElementExpression::Number(FieldPrime::from(6)).into()
+                    FieldElementExpression::Number(FieldPrime::from(6)).into(),
+                    None
                 )]
             );
 
@@ -320,12 +375,13 @@ mod tests {
             let mut u = Unroller::new();
 
             let s: TypedStatement<FieldPrime> =
-                TypedStatement::Declaration(Variable::field_element("a".into()));
+                TypedStatement::Declaration(Variable::field_element("a".into()), None);
             assert_eq!(u.fold_statement(s), vec![]);
 
             let s = TypedStatement::Definition(
                 TypedAssignee::Identifier(Variable::field_element("a".into())),
                 FieldElementExpression::Number(FieldPrime::from(5)).into(),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -333,7 +389,8 @@ mod tests {
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("a").version(0)
                     )),
-                    FieldElementExpression::Number(FieldPrime::from(5)).into()
+                    FieldElementExpression::Number(FieldPrime::from(5)).into(),
+                    None
                 )]
             );
 
@@ -344,6 +401,7 @@ mod tests {
                     box FieldElementExpression::Number(FieldPrime::from(1)),
                 )
                 .into(),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -355,7 +413,8 @@ mod tests {
                         box FieldElementExpression::Identifier(Identifier::from("a").version(0)),
                         box FieldElementExpression::Number(FieldPrime::from(1))
                     )
-                    .into()
+                    .into(),
+                    None
                 )]
             );
         }
@@ -375,12 +434,13 @@ mod tests {
             let mut u = Unroller::new();
 
             let s: TypedStatement<FieldPrime> =
-                TypedStatement::Declaration(Variable::field_element("a".into()));
+                TypedStatement::Declaration(Variable::field_element("a".into()), None);
             assert_eq!(u.fold_statement(s), vec![]);
 
             let s = TypedStatement::Definition(
                 TypedAssignee::Identifier(Variable::field_element("a".into())),
                 FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -388,7 +448,8 @@ mod tests {
                     TypedAssignee::Identifier(Variable::field_element(
                         Identifier::from("a").version(0)
                     )),
-                    FieldElementExpression::Number(FieldPrime::from(2)).into()
+                    FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                    None
                 )]
             );
 
@@ -399,6 +460,7 @@ mod tests {
                     vec![FieldElementExpression::Identifier("a".into()).into()],
                     vec![Type::FieldElement],
                 ),
+                None,
             );
             assert_eq!(
                 u.fold_statement(s),
@@ -411,7 +473,8 @@ mod tests {
                                 .into()
                         ],
                         vec![Type::FieldElement],
-                    )
+                    ),
+                    None
                 )]
             );
         }
@@ -428,7 +491,7 @@ mod tests {
             let mut u = Unroller::new();
 
             let s: TypedStatement<FieldPrime> =
-                TypedStatement::Declaration(Variable::field_array("a".into(), 2));
+                TypedStatement::Declaration(Variable::field_array("a".into(), 2), None);
             assert_eq!(u.fold_statement(s), vec![]);
 
             let s = TypedStatement::Definition(
@@ -441,6 +504,7 @@ mod tests {
                     ],
                 )
                 .into(),
+                None,
             );
 
             assert_eq!(
@@ -457,7 +521,8 @@ mod tests {
                             FieldElementExpression::Number(FieldPrime::from(1))
                         ]
                     )
-                    .into()
+                    .into(),
+                    None
                 )]
             );
 
@@ -467,6 +532,7 @@ mod tests {
                     box FieldElementExpression::Number(FieldPrime::from(1)),
                 ),
                 FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                None,
             );
 
             assert_eq!(
@@ -509,7 +575,8 @@ mod tests {
                             ),
                         ]
                     )
-                    .into()
+                    .into(),
+                    None
                 )]
             );
         }
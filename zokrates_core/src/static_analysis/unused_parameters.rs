@@ -0,0 +1,129 @@
+//! Module containing the detection of unused function parameters
+//!
+//! Unused parameters are only reported, as removing them would change the signature of the function
+
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use std::collections::HashSet;
+use std::fmt;
+use zokrates_field::field::Field;
+
+#[derive(Debug, PartialEq)]
+pub struct UnusedParameter {
+    pub function: String,
+    pub parameter: String,
+}
+
+impl fmt::Display for UnusedParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "parameter `{}` of function `{}` is never used",
+            self.parameter, self.function
+        )
+    }
+}
+
+pub struct UnusedParameters<'ast> {
+    // identifiers which are read in the current function
+    read: HashSet<Identifier<'ast>>,
+}
+
+impl<'ast> UnusedParameters<'ast> {
+    fn new() -> Self {
+        UnusedParameters {
+            read: HashSet::new(),
+        }
+    }
+
+    pub fn detect<T: Field>(p: &TypedProg<'ast, T>) -> Vec<UnusedParameter> {
+        p.functions
+            .iter()
+            .flat_map(|f| {
+                let mut detector = UnusedParameters::new();
+                detector.fold_function(f.clone());
+
+                f.arguments
+                    .iter()
+                    .filter(|a| !detector.read.contains(&a.id.id))
+                    .map(|a| UnusedParameter {
+                        function: f.id.to_string(),
+                        parameter: a.id.id.id.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for UnusedParameters<'ast> {
+    // only identifiers found in expressions are reads, declared and defined variables are skipped
+    fn fold_variable(&mut self, v: Variable<'ast>) -> Variable<'ast> {
+        v
+    }
+
+    fn fold_name(&mut self, n: Identifier<'ast>) -> Identifier<'ast> {
+        self.read.insert(n.clone());
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Signature, Type};
+    use zokrates_field::field::FieldPrime;
+
+    #[test]
+    fn one_unused() {
+        // def main(a, b):
+        //   c = a + 1
+        //   b = 42
+        //   return c
+
+        let main: TypedFunction<FieldPrime> = TypedFunction {
+            id: "main",
+            arguments: vec![
+                Parameter::private(Variable::field_element("a".into())),
+                Parameter::private(Variable::field_element("b".into())),
+            ],
+            statements: vec![
+                TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("c".into())),
+                    FieldElementExpression::Add(
+                        box FieldElementExpression::Identifier("a".into()),
+                        box FieldElementExpression::Number(FieldPrime::from(1)),
+                    )
+                    .into(),
+                    None,
+                ),
+                TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("b".into())),
+                    FieldElementExpression::Number(FieldPrime::from(42)).into(),
+                    None,
+                ),
+                TypedStatement::Return(
+                    vec![FieldElementExpression::Identifier("c".into()).into()],
+                    None,
+                ),
+            ],
+            signature: Signature::new()
+                .inputs(vec![Type::FieldElement, Type::FieldElement])
+                .outputs(vec![Type::FieldElement]),
+        };
+
+        let p = TypedProg {
+            functions: vec![main],
+            imports: vec![],
+            imported_functions: vec![],
+        };
+
+        assert_eq!(
+            UnusedParameters::detect(&p),
+            vec![UnusedParameter {
+                function: String::from("main"),
+                parameter: String::from("b"),
+            }]
+        );
+    }
+}
@@ -109,20 +109,29 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
     s: TypedStatement<'ast, T>,
 ) -> Vec<TypedStatement<'ast, T>> {
     let res = match s {
-        TypedStatement::Return(expressions) => TypedStatement::Return(
+        TypedStatement::Return(expressions, span) => TypedStatement::Return(
             expressions
                 .into_iter()
                 .map(|e| f.fold_expression(e))
                 .collect(),
+            span,
         ),
-        TypedStatement::Definition(a, e) => {
-            TypedStatement::Definition(f.fold_assignee(a), f.fold_expression(e))
+        TypedStatement::Definition(a, e, span) => {
+            TypedStatement::Definition(f.fold_assignee(a), f.fold_expression(e), span)
         }
-        TypedStatement::Declaration(v) => TypedStatement::Declaration(f.fold_variable(v)),
-        TypedStatement::Condition(left, right) => {
-            TypedStatement::Condition(f.fold_expression(left), f.fold_expression(right))
+        TypedStatement::Declaration(v, span) => {
+            TypedStatement::Declaration(f.fold_variable(v), span)
         }
-        TypedStatement::For(v, from, to, statements) => TypedStatement::For(
+        TypedStatement::Condition(left, right, message, span) => TypedStatement::Condition(
+            f.fold_expression(left),
+            f.fold_expression(right),
+            message,
+            span,
+        ),
+        TypedStatement::Assert(e, message, span) => {
+            TypedStatement::Assert(f.fold_boolean_expression(e), message, span)
+        }
+        TypedStatement::For(v, from, to, statements, span) => TypedStatement::For(
             f.fold_variable(v),
             from,
             to,
@@ -130,11 +139,15 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
                 .into_iter()
                 .flat_map(|s| f.fold_statement(s))
                 .collect(),
+            span,
         ),
-        TypedStatement::MultipleDefinition(variables, elist) => TypedStatement::MultipleDefinition(
-            variables.into_iter().map(|v| f.fold_variable(v)).collect(),
-            f.fold_expression_list(elist),
-        ),
+        TypedStatement::MultipleDefinition(variables, elist, span) => {
+            TypedStatement::MultipleDefinition(
+                variables.into_iter().map(|v| f.fold_variable(v)).collect(),
+                f.fold_expression_list(elist),
+                span,
+            )
+        }
     };
     vec![res]
 }
@@ -165,6 +178,23 @@ pub fn fold_field_array_expression<'ast, T: Field, F: Folder<'ast, T>>(
                 box f.fold_field_array_expression(alternative),
             )
         }
+        FieldElementArrayExpression::Repeat(size, box e) => {
+            FieldElementArrayExpression::Repeat(size, box f.fold_field_expression(e))
+        }
+        FieldElementArrayExpression::Concat(box left, box right) => {
+            FieldElementArrayExpression::Concat(
+                box f.fold_field_array_expression(left),
+                box f.fold_field_array_expression(right),
+            )
+        }
+        FieldElementArrayExpression::Slice(size, box array, box from, box to) => {
+            FieldElementArrayExpression::Slice(
+                size,
+                box f.fold_field_array_expression(array),
+                box f.fold_field_expression(from),
+                box f.fold_field_expression(to),
+            )
+        }
     }
 }
 
@@ -202,6 +232,20 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_field_expression(e2);
             FieldElementExpression::Pow(box e1, box e2)
         }
+        FieldElementExpression::Max(box e1, box e2) => {
+            let e1 = f.fold_field_expression(e1);
+            let e2 = f.fold_field_expression(e2);
+            FieldElementExpression::Max(box e1, box e2)
+        }
+        FieldElementExpression::Min(box e1, box e2) => {
+            let e1 = f.fold_field_expression(e1);
+            let e2 = f.fold_field_expression(e2);
+            FieldElementExpression::Min(box e1, box e2)
+        }
+        FieldElementExpression::Neg(box e) => {
+            let e = f.fold_field_expression(e);
+            FieldElementExpression::Neg(box e)
+        }
         FieldElementExpression::IfElse(box cond, box cons, box alt) => {
             let cond = f.fold_boolean_expression(cond);
             let cons = f.fold_field_expression(cons);
@@ -212,11 +256,19 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let exps = exps.into_iter().map(|e| f.fold_expression(e)).collect();
             FieldElementExpression::FunctionCall(id, exps)
         }
+        FieldElementExpression::SelectConstant(values, box index) => {
+            let index = f.fold_field_expression(index);
+            FieldElementExpression::SelectConstant(values, box index)
+        }
         FieldElementExpression::Select(box array, box index) => {
             let array = f.fold_field_array_expression(array);
             let index = f.fold_field_expression(index);
             FieldElementExpression::Select(box array, box index)
         }
+        FieldElementExpression::FromBoolean(box e) => {
+            let e = f.fold_boolean_expression(e);
+            FieldElementExpression::FromBoolean(box e)
+        }
     }
 }
 
@@ -262,10 +314,34 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_boolean_expression(e2);
             BooleanExpression::And(box e1, box e2)
         }
+        BooleanExpression::BoolEq(box e1, box e2) => {
+            let e1 = f.fold_boolean_expression(e1);
+            let e2 = f.fold_boolean_expression(e2);
+            BooleanExpression::BoolEq(box e1, box e2)
+        }
         BooleanExpression::Not(box e) => {
             let e = f.fold_boolean_expression(e);
             BooleanExpression::Not(box e)
         }
+        BooleanExpression::IfElse(box cond, box cons, box alt) => {
+            let cond = f.fold_boolean_expression(cond);
+            let cons = f.fold_boolean_expression(cons);
+            let alt = f.fold_boolean_expression(alt);
+            BooleanExpression::IfElse(box cond, box cons, box alt)
+        }
+        BooleanExpression::FromField(box e) => {
+            let e = f.fold_field_expression(e);
+            BooleanExpression::FromField(box e)
+        }
+        BooleanExpression::ArrayEq(box e1, box e2) => {
+            let e1 = f.fold_field_array_expression(e1);
+            let e2 = f.fold_field_array_expression(e2);
+            BooleanExpression::ArrayEq(box e1, box e2)
+        }
+        BooleanExpression::FunctionCall(id, exps) => {
+            let exps = exps.into_iter().map(|e| f.fold_expression(e)).collect();
+            BooleanExpression::FunctionCall(id, exps)
+        }
     }
 }
 
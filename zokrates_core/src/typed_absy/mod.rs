@@ -15,16 +15,26 @@ use crate::types::Signature;
 
 use crate::flat_absy::*;
 use crate::imports::Import;
+use crate::parser::Position;
 use crate::types::Type;
+use bincode::{deserialize, serialize, Infinite};
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json;
+use std::collections::HashSet;
 use std::fmt;
 use zokrates_field::field::Field;
 
 pub use self::folder::Folder;
 
-#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+/// Start and end positions of a statement in the source code, like `absy::Node::pos`
+pub type Span = (Position, Position);
+
+#[derive(Debug, PartialEq, Clone, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Identifier<'ast> {
     pub id: &'ast str,
     pub version: usize,
+    #[serde(borrow)]
     pub stack: Vec<(&'ast str, Signature, usize)>,
 }
 
@@ -64,14 +74,155 @@ impl<'ast> Identifier<'ast> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(bound = "")]
 pub struct TypedProg<'ast, T: Field> {
     /// Functions of the program
     pub functions: Vec<TypedFunction<'ast, T>>,
     pub imports: Vec<Import>,
+    /// Imported functions are already flattened, only their ids are serialized
+    #[serde(serialize_with = "serialize_function_ids")]
     pub imported_functions: Vec<FlatFunction<T>>,
 }
 
+// field elements are serialized as hexadecimal strings of the same length, which do not depend on the
+// representation of the field
+fn serialize_hex<T: Field, S: Serializer>(n: &T, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&n.to_hex_string())
+}
+
+fn serialize_hex_vec<T: Field, S: Serializer>(values: &[T], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(values.iter().map(|n| n.to_hex_string()))
+}
+
+fn deserialize_hex<'de, T: Field, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+    let s = String::deserialize(d)?;
+    T::from_hex_string(&s).map_err(D::Error::custom)
+}
+
+fn deserialize_hex_vec<'de, T: Field, D: Deserializer<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    Vec::<String>::deserialize(d)?
+        .iter()
+        .map(|s| T::from_hex_string(s).map_err(D::Error::custom))
+        .collect()
+}
+
+fn serialize_function_ids<T: Field, S: Serializer>(
+    functions: &[FlatFunction<T>],
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_seq(functions.iter().map(|f| &f.id))
+}
+
+/// Size of the public interface of a program, as seen before flattening
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceSummary {
+    /// Number of `Condition` and `Assert` statements in the main function
+    pub conditions: usize,
+    /// Number of public field elements passed to the main function
+    pub inputs: usize,
+    /// Number of field elements returned by the main function
+    pub outputs: usize,
+}
+
+impl<'ast, T: Field> TypedProg<'ast, T> {
+    /// Returns the program as JSON, for external tools to analyse. Field elements are written as hexadecimal
+    /// strings, like `Field::to_hex_string` returns them
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a typed program should serialize to JSON")
+    }
+
+    /// Returns the function named `name`. A name can be overloaded with different signatures, in which case this is
+    /// the first function defined with it
+    pub fn function(&self, name: &str) -> Option<&TypedFunction<'ast, T>> {
+        self.functions.iter().find(|f| f.id == name)
+    }
+
+    /// Returns the function named `name` like `function`, mutably
+    pub fn function_mut(&mut self, name: &str) -> Option<&mut TypedFunction<'ast, T>> {
+        self.functions.iter_mut().find(|f| f.id == name)
+    }
+
+    /// Checks that no two functions have the same name and signature, which would make calls to them ambiguous
+    pub fn check_unique_functions(&self) -> Result<(), String> {
+        let mut slugs = HashSet::new();
+
+        match self.functions.iter().find(|f| !slugs.insert(f.to_slug())) {
+            Some(f) => Err(format!(
+                "Duplicate definition for function {} with signature {}",
+                f.id, f.signature
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the number of field elements passed publicly to the main function, which are the public inputs of the
+    /// compiled circuit
+    pub fn public_input_count(&self) -> usize {
+        self.function("main")
+            .expect("a program should have a main function")
+            .arguments
+            .iter()
+            .filter(|p| !p.private)
+            .map(|p| p.id.get_type().get_primitive_count())
+            .sum()
+    }
+
+    /// Returns the number of conditions, public inputs and outputs of the main function
+    pub fn interface_summary(&self) -> InterfaceSummary {
+        let main = self
+            .function("main")
+            .expect("a program should have a main function");
+
+        // statements inside loop bodies are counted once, regardless of the number of iterations
+        fn count_conditions<'ast, T: Field>(statements: &Vec<TypedStatement<'ast, T>>) -> usize {
+            statements
+                .iter()
+                .map(|s| match s {
+                    TypedStatement::Condition(..) | TypedStatement::Assert(..) => 1,
+                    TypedStatement::For(_, _, _, statements, _) => count_conditions(statements),
+                    _ => 0,
+                })
+                .sum()
+        }
+
+        InterfaceSummary {
+            conditions: count_conditions(&main.statements),
+            inputs: self.public_input_count(),
+            outputs: main
+                .signature
+                .outputs
+                .iter()
+                .map(|t| t.get_primitive_count())
+                .sum(),
+        }
+    }
+}
+
+impl<'ast, T: Field + Serialize + DeserializeOwned> TypedProg<'ast, T> {
+    /// Returns the program in a compact binary form which `from_bytes` reads back, to cache it between compilations.
+    /// Unlike `to_json`, this includes the bodies of the imported functions
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize(
+            &(&self.functions, &self.imports, &self.imported_functions),
+            Infinite,
+        )
+        .expect("a typed program should serialize to bytes")
+    }
+
+    /// Reads back a program returned by `to_bytes`. Identifiers borrow from `bytes`, which must outlive the program
+    pub fn from_bytes(bytes: &'ast [u8]) -> Result<Self, String> {
+        let (functions, imports, imported_functions) =
+            deserialize(bytes).map_err(|e| format!("could not read typed program: {}", e))?;
+
+        Ok(TypedProg {
+            functions,
+            imports,
+            imported_functions,
+        })
+    }
+}
+
 impl<'ast, T: Field> fmt::Display for TypedProg<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut res = vec![];
@@ -121,7 +272,8 @@ impl<'ast, T: Field> fmt::Debug for TypedProg<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub struct TypedFunction<'ast, T: Field> {
     /// Name of the program
     pub id: FunctionIdentifier<'ast>,
@@ -175,7 +327,8 @@ impl<'ast, T: Field> fmt::Debug for TypedFunction<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum TypedAssignee<'ast, T: Field> {
     Identifier(Variable<'ast>),
     ArrayElement(
@@ -214,20 +367,63 @@ impl<'ast, T: Field> fmt::Display for TypedAssignee<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum TypedStatement<'ast, T: Field> {
-    Return(Vec<TypedExpression<'ast, T>>),
-    Definition(TypedAssignee<'ast, T>, TypedExpression<'ast, T>),
-    Declaration(Variable<'ast>),
-    Condition(TypedExpression<'ast, T>, TypedExpression<'ast, T>),
-    For(Variable<'ast>, T, T, Vec<TypedStatement<'ast, T>>),
-    MultipleDefinition(Vec<Variable<'ast>>, TypedExpressionList<'ast, T>),
+    Return(Vec<TypedExpression<'ast, T>>, Option<Span>),
+    Definition(TypedAssignee<'ast, T>, TypedExpression<'ast, T>, Option<Span>),
+    Declaration(Variable<'ast>, Option<Span>),
+    // the optional message explains the condition to the user when it cannot be satisfied
+    Condition(
+        TypedExpression<'ast, T>,
+        TypedExpression<'ast, T>,
+        Option<String>,
+        Option<Span>,
+    ),
+    // an assertion written by the user, kept apart from the conditions generated by the compiler
+    Assert(BooleanExpression<'ast, T>, Option<String>, Option<Span>),
+    For(
+        Variable<'ast>,
+        #[serde(
+            serialize_with = "serialize_hex",
+            deserialize_with = "deserialize_hex"
+        )]
+        T,
+        #[serde(
+            serialize_with = "serialize_hex",
+            deserialize_with = "deserialize_hex"
+        )]
+        T,
+        Vec<TypedStatement<'ast, T>>,
+        Option<Span>,
+    ),
+    MultipleDefinition(
+        Vec<Variable<'ast>>,
+        TypedExpressionList<'ast, T>,
+        Option<Span>,
+    ),
+}
+
+impl<'ast, T: Field> TypedStatement<'ast, T> {
+    /// Returns the position in the source code of the statement this one was checked from, if any.
+    /// Statements created during static analysis take the position of the statement they come from
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            TypedStatement::Return(_, span)
+            | TypedStatement::Definition(_, _, span)
+            | TypedStatement::Declaration(_, span)
+            | TypedStatement::Condition(_, _, _, span)
+            | TypedStatement::Assert(_, _, span)
+            | TypedStatement::For(_, _, _, _, span)
+            | TypedStatement::MultipleDefinition(_, _, span) => span,
+        }
+    }
 }
 
 impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            TypedStatement::Return(ref exprs) => {
+            TypedStatement::Return(ref exprs, _) => {
                 r#try!(write!(f, "Return("));
                 for (i, expr) in exprs.iter().enumerate() {
                     r#try!(write!(f, "{}", expr));
@@ -237,21 +433,24 @@ impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
                 }
                 write!(f, ")")
             }
-            TypedStatement::Declaration(ref var) => write!(f, "Declaration({:?})", var),
-            TypedStatement::Definition(ref lhs, ref rhs) => {
+            TypedStatement::Declaration(ref var, _) => write!(f, "Declaration({:?})", var),
+            TypedStatement::Definition(ref lhs, ref rhs, _) => {
                 write!(f, "Definition({:?}, {:?})", lhs, rhs)
             }
-            TypedStatement::Condition(ref lhs, ref rhs) => {
-                write!(f, "Condition({:?}, {:?})", lhs, rhs)
+            TypedStatement::Condition(ref lhs, ref rhs, ref message, _) => {
+                write!(f, "Condition({:?}, {:?}, {:?})", lhs, rhs, message)
             }
-            TypedStatement::For(ref var, ref start, ref stop, ref list) => {
+            TypedStatement::Assert(ref e, ref message, _) => {
+                write!(f, "Assert({:?}, {:?})", e, message)
+            }
+            TypedStatement::For(ref var, ref start, ref stop, ref list, _) => {
                 r#try!(write!(f, "for {:?} in {:?}..{:?} do\n", var, start, stop));
                 for l in list {
                     r#try!(write!(f, "\t\t{:?}\n", l));
                 }
                 write!(f, "\tendfor")
             }
-            TypedStatement::MultipleDefinition(ref lhs, ref rhs) => {
+            TypedStatement::MultipleDefinition(ref lhs, ref rhs, _) => {
                 write!(f, "MultipleDefinition({:?}, {:?})", lhs, rhs)
             }
         }
@@ -261,7 +460,7 @@ impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
 impl<'ast, T: Field> fmt::Display for TypedStatement<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            TypedStatement::Return(ref exprs) => {
+            TypedStatement::Return(ref exprs, _) => {
                 r#try!(write!(f, "return "));
                 for (i, expr) in exprs.iter().enumerate() {
                     r#try!(write!(f, "{}", expr));
@@ -271,17 +470,24 @@ impl<'ast, T: Field> fmt::Display for TypedStatement<'ast, T> {
                 }
                 write!(f, "")
             }
-            TypedStatement::Declaration(ref var) => write!(f, "{}", var),
-            TypedStatement::Definition(ref lhs, ref rhs) => write!(f, "{} = {}", lhs, rhs),
-            TypedStatement::Condition(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
-            TypedStatement::For(ref var, ref start, ref stop, ref list) => {
+            TypedStatement::Declaration(ref var, _) => write!(f, "{}", var),
+            TypedStatement::Definition(ref lhs, ref rhs, _) => write!(f, "{} = {}", lhs, rhs),
+            TypedStatement::Condition(ref lhs, ref rhs, ref message, _) => match message {
+                Some(message) => write!(f, "{} == {} // {}", lhs, rhs, message),
+                None => write!(f, "{} == {}", lhs, rhs),
+            },
+            TypedStatement::Assert(ref e, ref message, _) => match message {
+                Some(message) => write!(f, "assert({}) // {}", e, message),
+                None => write!(f, "assert({})", e),
+            },
+            TypedStatement::For(ref var, ref start, ref stop, ref list, _) => {
                 r#try!(write!(f, "for {} in {}..{} do\n", var, start, stop));
                 for l in list {
                     r#try!(write!(f, "\t\t{}\n", l));
                 }
                 write!(f, "\tendfor")
             }
-            TypedStatement::MultipleDefinition(ref ids, ref rhs) => {
+            TypedStatement::MultipleDefinition(ref ids, ref rhs, _) => {
                 for (i, id) in ids.iter().enumerate() {
                     r#try!(write!(f, "{}", id));
                     if i < ids.len() - 1 {
@@ -298,7 +504,8 @@ pub trait Typed {
     fn get_type(&self) -> Type;
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum TypedExpression<'ast, T: Field> {
     Boolean(BooleanExpression<'ast, T>),
     FieldElement(FieldElementExpression<'ast, T>),
@@ -323,6 +530,18 @@ impl<'ast, T: Field> From<FieldElementArrayExpression<'ast, T>> for TypedExpress
     }
 }
 
+impl<'ast, T: Field> TypedExpression<'ast, T> {
+    /// Returns whether this expression is a constant: a boolean value, a number, or an array of numbers
+    pub fn is_constant(&self) -> bool {
+        match *self {
+            TypedExpression::Boolean(BooleanExpression::Value(..))
+            | TypedExpression::FieldElement(FieldElementExpression::Number(..)) => true,
+            TypedExpression::FieldElementArray(ref a) => a.is_constant(),
+            _ => false,
+        }
+    }
+}
+
 impl<'ast, T: Field> fmt::Display for TypedExpression<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -360,6 +579,9 @@ impl<'ast, T: Field> Typed for FieldElementArrayExpression<'ast, T> {
             FieldElementArrayExpression::Value(n, _) => Type::FieldElementArray(n),
             FieldElementArrayExpression::FunctionCall(n, _, _) => Type::FieldElementArray(n),
             FieldElementArrayExpression::IfElse(_, ref consequence, _) => consequence.get_type(),
+            FieldElementArrayExpression::Repeat(n, _) => Type::FieldElementArray(n),
+            FieldElementArrayExpression::Slice(n, ..) => Type::FieldElementArray(n),
+            FieldElementArrayExpression::Concat(..) => Type::FieldElementArray(self.size()),
         }
     }
 }
@@ -368,7 +590,8 @@ pub trait MultiTyped {
     fn get_types(&self) -> &Vec<Type>;
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum TypedExpressionList<'ast, T: Field> {
     FunctionCall(String, Vec<TypedExpression<'ast, T>>, Vec<Type>),
 }
@@ -381,9 +604,16 @@ impl<'ast, T: Field> MultiTyped for TypedExpressionList<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum FieldElementExpression<'ast, T: Field> {
-    Number(T),
+    Number(
+        #[serde(
+            serialize_with = "serialize_hex",
+            deserialize_with = "deserialize_hex"
+        )]
+        T,
+    ),
     Identifier(Identifier<'ast>),
     Add(
         Box<FieldElementExpression<'ast, T>>,
@@ -405,19 +635,41 @@ pub enum FieldElementExpression<'ast, T: Field> {
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    // the larger and smaller of two elements in the ordering of their representatives in `0..p`
+    Max(
+        Box<FieldElementExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+    ),
+    Min(
+        Box<FieldElementExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+    ),
+    Neg(Box<FieldElementExpression<'ast, T>>),
     IfElse(
         Box<BooleanExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
     FunctionCall(String, Vec<TypedExpression<'ast, T>>),
+    // select in an array whose values are all known constants, at a non-constant index
+    SelectConstant(
+        #[serde(
+            serialize_with = "serialize_hex_vec",
+            deserialize_with = "deserialize_hex_vec"
+        )]
+        Vec<T>,
+        Box<FieldElementExpression<'ast, T>>,
+    ),
     Select(
         Box<FieldElementArrayExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    // a boolean as 0 or 1
+    FromBoolean(Box<BooleanExpression<'ast, T>>),
 }
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum BooleanExpression<'ast, T: Field> {
     Identifier(Identifier<'ast>),
     Value(bool),
@@ -449,11 +701,29 @@ pub enum BooleanExpression<'ast, T: Field> {
         Box<BooleanExpression<'ast, T>>,
         Box<BooleanExpression<'ast, T>>,
     ),
+    BoolEq(
+        Box<BooleanExpression<'ast, T>>,
+        Box<BooleanExpression<'ast, T>>,
+    ),
     Not(Box<BooleanExpression<'ast, T>>),
+    IfElse(
+        Box<BooleanExpression<'ast, T>>,
+        Box<BooleanExpression<'ast, T>>,
+        Box<BooleanExpression<'ast, T>>,
+    ),
+    // a field element which is asserted to be 0 or 1
+    FromField(Box<FieldElementExpression<'ast, T>>),
+    // two arrays of the same size are equal if all their elements are
+    ArrayEq(
+        Box<FieldElementArrayExpression<'ast, T>>,
+        Box<FieldElementArrayExpression<'ast, T>>,
+    ),
+    FunctionCall(String, Vec<TypedExpression<'ast, T>>),
 }
 
 // for now we store the array size in the variants
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "'de: 'ast"))]
 pub enum FieldElementArrayExpression<'ast, T: Field> {
     Identifier(usize, Identifier<'ast>),
     Value(usize, Vec<FieldElementExpression<'ast, T>>),
@@ -463,6 +733,21 @@ pub enum FieldElementArrayExpression<'ast, T: Field> {
         Box<FieldElementArrayExpression<'ast, T>>,
         Box<FieldElementArrayExpression<'ast, T>>,
     ),
+    // `size` copies of the same element, without repeating it in the tree
+    Repeat(usize, Box<FieldElementExpression<'ast, T>>),
+    // the elements of the first array followed by those of the second one
+    Concat(
+        Box<FieldElementArrayExpression<'ast, T>>,
+        Box<FieldElementArrayExpression<'ast, T>>,
+    ),
+    // the `size` elements of an array from the first bound included to the second one excluded. The bounds are
+    // numbers once checked, as they determine the size of the slice
+    Slice(
+        usize,
+        Box<FieldElementArrayExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+    ),
 }
 
 impl<'ast, T: Field> FieldElementArrayExpression<'ast, T> {
@@ -470,8 +755,49 @@ impl<'ast, T: Field> FieldElementArrayExpression<'ast, T> {
         match *self {
             FieldElementArrayExpression::Identifier(s, _)
             | FieldElementArrayExpression::Value(s, _)
-            | FieldElementArrayExpression::FunctionCall(s, ..) => s,
+            | FieldElementArrayExpression::FunctionCall(s, ..)
+            | FieldElementArrayExpression::Repeat(s, _)
+            | FieldElementArrayExpression::Slice(s, ..) => s,
             FieldElementArrayExpression::IfElse(_, ref consequence, _) => consequence.size(),
+            FieldElementArrayExpression::Concat(ref left, ref right) => left.size() + right.size(),
+        }
+    }
+
+    /// Returns whether `as_constant_vec` returns the values of this array, without collecting them
+    pub fn is_constant(&self) -> bool {
+        match *self {
+            FieldElementArrayExpression::Value(_, ref v) => v.iter().all(|e| match e {
+                FieldElementExpression::Number(..) => true,
+                _ => false,
+            }),
+            FieldElementArrayExpression::Repeat(_, box FieldElementExpression::Number(..)) => true,
+            FieldElementArrayExpression::Concat(ref left, ref right) => {
+                left.is_constant() && right.is_constant()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the values of this array if it is a `Value` made only of numbers, a `Repeat` of a number, or a
+    /// `Concat` of such arrays
+    pub fn as_constant_vec(&self) -> Option<Vec<T>> {
+        match *self {
+            FieldElementArrayExpression::Value(_, ref v) => v
+                .iter()
+                .map(|e| match e {
+                    FieldElementExpression::Number(n) => Some(n.clone()),
+                    _ => None,
+                })
+                .collect(),
+            FieldElementArrayExpression::Repeat(size, box FieldElementExpression::Number(ref n)) => {
+                Some(vec![n.clone(); size])
+            }
+            FieldElementArrayExpression::Concat(ref left, ref right) => {
+                let mut values = left.as_constant_vec()?;
+                values.extend(right.as_constant_vec()?);
+                Some(values)
+            }
+            _ => None,
         }
     }
 }
@@ -486,6 +812,9 @@ impl<'ast, T: Field> fmt::Display for FieldElementExpression<'ast, T> {
             FieldElementExpression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
             FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
+            FieldElementExpression::Max(ref lhs, ref rhs) => write!(f, "max({}, {})", lhs, rhs),
+            FieldElementExpression::Min(ref lhs, ref rhs) => write!(f, "min({}, {})", lhs, rhs),
+            FieldElementExpression::Neg(ref e) => write!(f, "(-{})", e),
             FieldElementExpression::IfElse(ref condition, ref consequent, ref alternative) => {
                 write!(
                     f,
@@ -503,7 +832,18 @@ impl<'ast, T: Field> fmt::Display for FieldElementExpression<'ast, T> {
                 }
                 write!(f, ")")
             }
+            FieldElementExpression::SelectConstant(ref values, ref index) => write!(
+                f,
+                "[{}][{}]",
+                values
+                    .iter()
+                    .map(|o| o.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                index
+            ),
             FieldElementExpression::Select(ref id, ref index) => write!(f, "{}[{}]", id, index),
+            FieldElementExpression::FromBoolean(ref e) => write!(f, "field({})", e),
         }
     }
 }
@@ -519,8 +859,26 @@ impl<'ast, T: Field> fmt::Display for BooleanExpression<'ast, T> {
             BooleanExpression::Gt(ref lhs, ref rhs) => write!(f, "{} > {}", lhs, rhs),
             BooleanExpression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
             BooleanExpression::And(ref lhs, ref rhs) => write!(f, "{} && {}", lhs, rhs),
+            BooleanExpression::BoolEq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
             BooleanExpression::Not(ref exp) => write!(f, "!{}", exp),
+            BooleanExpression::IfElse(ref condition, ref consequent, ref alternative) => write!(
+                f,
+                "if {} then {} else {} fi",
+                condition, consequent, alternative
+            ),
             BooleanExpression::Value(b) => write!(f, "{}", b),
+            BooleanExpression::FromField(ref e) => write!(f, "bool({})", e),
+            BooleanExpression::ArrayEq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            BooleanExpression::FunctionCall(ref i, ref p) => {
+                r#try!(write!(f, "{}(", i,));
+                for (i, param) in p.iter().enumerate() {
+                    r#try!(write!(f, "{}", param));
+                    if i < p.len() - 1 {
+                        r#try!(write!(f, ", "));
+                    }
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -555,6 +913,13 @@ impl<'ast, T: Field> fmt::Display for FieldElementArrayExpression<'ast, T> {
                     condition, consequent, alternative
                 )
             }
+            FieldElementArrayExpression::Repeat(size, ref e) => write!(f, "[{}; {}]", e, size),
+            FieldElementArrayExpression::Concat(ref left, ref right) => {
+                write!(f, "({} ++ {})", left, right)
+            }
+            FieldElementArrayExpression::Slice(_, ref array, ref from, ref to) => {
+                write!(f, "{}[{}..{}]", array, from, to)
+            }
         }
     }
 }
@@ -568,7 +933,7 @@ impl<'ast, T: Field> fmt::Debug for BooleanExpression<'ast, T> {
 impl<'ast, T: Field> fmt::Debug for FieldElementExpression<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            FieldElementExpression::Number(ref i) => write!(f, "Num({})", i),
+            FieldElementExpression::Number(ref i) => write!(f, "Num({})", i.to_hex_string()),
             FieldElementExpression::Identifier(ref var) => write!(f, "Ide({})", var),
             FieldElementExpression::Add(ref lhs, ref rhs) => write!(f, "Add({:?}, {:?})", lhs, rhs),
             FieldElementExpression::Sub(ref lhs, ref rhs) => write!(f, "Sub({:?}, {:?})", lhs, rhs),
@@ -577,6 +942,9 @@ impl<'ast, T: Field> fmt::Debug for FieldElementExpression<'ast, T> {
             }
             FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "Div({:?}, {:?})", lhs, rhs),
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "Pow({:?}, {:?})", lhs, rhs),
+            FieldElementExpression::Max(ref lhs, ref rhs) => write!(f, "Max({:?}, {:?})", lhs, rhs),
+            FieldElementExpression::Min(ref lhs, ref rhs) => write!(f, "Min({:?}, {:?})", lhs, rhs),
+            FieldElementExpression::Neg(ref e) => write!(f, "Neg({:?})", e),
             FieldElementExpression::IfElse(ref condition, ref consequent, ref alternative) => {
                 write!(
                     f,
@@ -589,9 +957,13 @@ impl<'ast, T: Field> fmt::Debug for FieldElementExpression<'ast, T> {
                 r#try!(f.debug_list().entries(p.iter()).finish());
                 write!(f, ")")
             }
+            FieldElementExpression::SelectConstant(ref values, ref index) => {
+                write!(f, "SelectConstant({:?}, {:?})", values, index)
+            }
             FieldElementExpression::Select(ref id, ref index) => {
                 write!(f, "Select({:?}, {:?})", id, index)
             }
+            FieldElementExpression::FromBoolean(ref e) => write!(f, "FromBoolean({:?})", e),
         }
     }
 }
@@ -613,6 +985,15 @@ impl<'ast, T: Field> fmt::Debug for FieldElementArrayExpression<'ast, T> {
                     condition, consequent, alternative
                 )
             }
+            FieldElementArrayExpression::Repeat(size, ref e) => {
+                write!(f, "Repeat({:?}, {:?})", size, e)
+            }
+            FieldElementArrayExpression::Concat(ref left, ref right) => {
+                write!(f, "Concat({:?}, {:?})", left, right)
+            }
+            FieldElementArrayExpression::Slice(size, ref array, ref from, ref to) => {
+                write!(f, "Slice({}, {:?}, {:?}, {:?})", size, array, from, to)
+            }
         }
     }
 }
@@ -651,3 +1032,438 @@ impl<'ast, T: Field> TypedFunction<'ast, T> {
         format!("{}_{}", self.id, self.signature.to_slug())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::field::FieldPrime;
+
+    #[cfg(test)]
+    mod is_constant {
+        use super::*;
+
+        #[test]
+        fn boolean() {
+            let e: TypedExpression<FieldPrime> = BooleanExpression::Value(true).into();
+            assert!(e.is_constant());
+
+            let e: TypedExpression<FieldPrime> = BooleanExpression::Identifier("a".into()).into();
+            assert!(!e.is_constant());
+        }
+
+        #[test]
+        fn field_element() {
+            let e: TypedExpression<FieldPrime> =
+                FieldElementExpression::Number(FieldPrime::from(42)).into();
+            assert!(e.is_constant());
+
+            let e: TypedExpression<FieldPrime> = FieldElementExpression::Add(
+                box FieldElementExpression::Number(FieldPrime::from(1)),
+                box FieldElementExpression::Number(FieldPrime::from(2)),
+            )
+            .into();
+            assert!(!e.is_constant());
+        }
+
+        #[test]
+        fn field_element_array() {
+            let e: TypedExpression<FieldPrime> = FieldElementArrayExpression::Value(
+                2,
+                vec![
+                    FieldElementExpression::Number(FieldPrime::from(1)),
+                    FieldElementExpression::Number(FieldPrime::from(2)),
+                ],
+            )
+            .into();
+            assert!(e.is_constant());
+
+            // partially constant arrays are not constant
+            let e: TypedExpression<FieldPrime> = FieldElementArrayExpression::Value(
+                2,
+                vec![
+                    FieldElementExpression::Number(FieldPrime::from(1)),
+                    FieldElementExpression::Identifier("a".into()),
+                ],
+            )
+            .into();
+            assert!(!e.is_constant());
+
+            let e: TypedExpression<FieldPrime> =
+                FieldElementArrayExpression::Identifier(2, "a".into()).into();
+            assert!(!e.is_constant());
+        }
+    }
+
+    #[cfg(test)]
+    mod as_constant_vec {
+        use super::*;
+
+        #[test]
+        fn constant() {
+            let a: FieldElementArrayExpression<FieldPrime> = FieldElementArrayExpression::Value(
+                2,
+                vec![
+                    FieldElementExpression::Number(FieldPrime::from(1)),
+                    FieldElementExpression::Number(FieldPrime::from(2)),
+                ],
+            );
+
+            assert_eq!(
+                a.as_constant_vec(),
+                Some(vec![FieldPrime::from(1), FieldPrime::from(2)])
+            );
+            assert!(a.is_constant());
+        }
+
+        #[test]
+        fn mixed() {
+            let a: FieldElementArrayExpression<FieldPrime> = FieldElementArrayExpression::Value(
+                2,
+                vec![
+                    FieldElementExpression::Number(FieldPrime::from(1)),
+                    FieldElementExpression::Identifier("a".into()),
+                ],
+            );
+
+            assert_eq!(a.as_constant_vec(), None);
+            assert!(!a.is_constant());
+
+            let a: FieldElementArrayExpression<FieldPrime> =
+                FieldElementArrayExpression::Identifier(2, "a".into());
+
+            assert_eq!(a.as_constant_vec(), None);
+            assert!(!a.is_constant());
+        }
+    }
+
+    #[cfg(test)]
+    mod size {
+        use super::*;
+
+        #[test]
+        fn slice_with_symbolic_bounds() {
+            // a[i..i + 2], whose bounds are not numbers
+            let i = || FieldElementExpression::Identifier("i".into());
+            let a: FieldElementArrayExpression<FieldPrime> = FieldElementArrayExpression::Slice(
+                2,
+                box FieldElementArrayExpression::Identifier(4, "a".into()),
+                box i(),
+                box FieldElementExpression::Add(
+                    box i(),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                ),
+            );
+
+            assert_eq!(a.size(), 2);
+            assert_eq!(a.get_type(), Type::FieldElementArray(2));
+        }
+    }
+
+    #[cfg(test)]
+    mod interface_summary {
+        use super::*;
+
+        #[test]
+        fn main_function() {
+            // def main(a, private b) -> (field):
+            //     a == b
+            //     a == 1
+            //     return a
+            let p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![TypedFunction {
+                    id: "main",
+                    arguments: vec![
+                        Parameter {
+                            id: Variable::field_element("a".into()),
+                            private: false,
+                        },
+                        Parameter::private(Variable::field_element("b".into())),
+                    ],
+                    statements: vec![
+                        TypedStatement::Condition(
+                            FieldElementExpression::Identifier("a".into()).into(),
+                            FieldElementExpression::Identifier("b".into()).into(),
+                            None,
+                            None,
+                        ),
+                        TypedStatement::Condition(
+                            FieldElementExpression::Identifier("a".into()).into(),
+                            FieldElementExpression::Number(FieldPrime::from(1)).into(),
+                            None,
+                            None,
+                        ),
+                        TypedStatement::Return(
+                            vec![FieldElementExpression::Identifier("a".into()).into()],
+                            None,
+                        ),
+                    ],
+                    signature: Signature::new()
+                        .inputs(vec![Type::FieldElement, Type::FieldElement])
+                        .outputs(vec![Type::FieldElement]),
+                }],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            assert_eq!(
+                p.interface_summary(),
+                InterfaceSummary {
+                    conditions: 2,
+                    inputs: 1,
+                    outputs: 1,
+                }
+            );
+        }
+
+        #[test]
+        fn public_inputs() {
+            // def main(a, private b, field[2] c) -> (field):
+            //     return a
+            let p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![TypedFunction {
+                    id: "main",
+                    arguments: vec![
+                        Parameter {
+                            id: Variable::field_element("a".into()),
+                            private: false,
+                        },
+                        Parameter::private(Variable::field_element("b".into())),
+                        Parameter {
+                            id: Variable::field_array("c".into(), 2),
+                            private: false,
+                        },
+                    ],
+                    statements: vec![TypedStatement::Return(
+                        vec![FieldElementExpression::Identifier("a".into()).into()],
+                        None,
+                    )],
+                    signature: Signature::new()
+                        .inputs(vec![
+                            Type::FieldElement,
+                            Type::FieldElement,
+                            Type::FieldElementArray(2),
+                        ])
+                        .outputs(vec![Type::FieldElement]),
+                }],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            // each element of a public array is a public input
+            assert_eq!(p.public_input_count(), 3);
+        }
+    }
+
+    #[cfg(test)]
+    mod function {
+        use super::*;
+
+        fn function(id: &'static str, signature: Signature) -> TypedFunction<'static, FieldPrime> {
+            TypedFunction {
+                id,
+                arguments: vec![],
+                statements: vec![],
+                signature,
+            }
+        }
+
+        #[test]
+        fn lookup() {
+            let mut p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![
+                    function("foo", Signature::new()),
+                    function("main", Signature::new()),
+                ],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            assert_eq!(p.function("main").map(|f| f.id), Some("main"));
+            assert!(p.function("bar").is_none());
+
+            p.function_mut("foo").unwrap().signature =
+                Signature::new().outputs(vec![Type::FieldElement]);
+            assert_eq!(p.functions[0].signature.outputs, vec![Type::FieldElement]);
+            assert!(p.function_mut("bar").is_none());
+        }
+
+        #[test]
+        fn unique() {
+            // overloading a name with another signature is allowed
+            let mut p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![
+                    function("foo", Signature::new()),
+                    function("foo", Signature::new().outputs(vec![Type::FieldElement])),
+                ],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            assert_eq!(p.check_unique_functions(), Ok(()));
+
+            p.functions.push(function("foo", Signature::new()));
+
+            assert_eq!(
+                p.check_unique_functions(),
+                Err(String::from("Duplicate definition for function foo with signature () -> ()"))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod to_json {
+        use super::*;
+
+        #[test]
+        fn program() {
+            // def main(private a) -> (field):
+            //     a == 1
+            //     return a + 2
+            let p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![TypedFunction {
+                    id: "main",
+                    arguments: vec![Parameter::private(Variable::field_element("a".into()))],
+                    statements: vec![
+                        TypedStatement::Condition(
+                            FieldElementExpression::Identifier("a".into()).into(),
+                            FieldElementExpression::Number(FieldPrime::from(1)).into(),
+                            None,
+                            None,
+                        ),
+                        TypedStatement::Return(
+                            vec![FieldElementExpression::Add(
+                                box FieldElementExpression::Identifier("a".into()),
+                                box FieldElementExpression::Number(FieldPrime::from(2)),
+                            )
+                            .into()],
+                            None,
+                        ),
+                    ],
+                    signature: Signature::new()
+                        .inputs(vec![Type::FieldElement])
+                        .outputs(vec![Type::FieldElement]),
+                }],
+                imports: vec![],
+                imported_functions: vec![],
+            };
+
+            assert_eq!(
+                p.to_json(),
+                concat!(
+                    r#"{"functions":[{"id":"main","#,
+                    r#""arguments":[{"id":{"id":{"id":"a","version":0,"stack":[]},"_type":"FieldElement"},"private":true}],"#,
+                    r#""statements":["#,
+                    r#"{"Condition":[{"FieldElement":{"Identifier":{"id":"a","version":0,"stack":[]}}},"#,
+                    r#"{"FieldElement":{"Number":"#,
+                    r#""0x0000000000000000000000000000000000000000000000000000000000000001"}},null,null]},"#,
+                    r#"{"Return":[[{"FieldElement":{"Add":[{"Identifier":{"id":"a","version":0,"stack":[]}},"#,
+                    r#"{"Number":"0x0000000000000000000000000000000000000000000000000000000000000002"}]}}],null]}],"#,
+                    r#""signature":{"inputs":["FieldElement"],"outputs":["FieldElement"]}}],"#,
+                    r#""imports":[],"imported_functions":[]}"#
+                )
+            );
+        }
+    }
+
+    mod to_bytes {
+        use super::*;
+        use crate::imports::Import;
+
+        #[test]
+        fn round_trip() {
+            // import "./foo.code" as foo
+            // def main(private field a, field[2] b) -> (field):
+            //     field c = 0
+            //     for field i in 0..2 do
+            //         c = c + [1, 2][i] * b[i]
+            //     endfor
+            //     a == c
+            //     return x + (-1), with x defined in a call to foo
+            let x = Identifier {
+                id: "x",
+                version: 3,
+                stack: vec![("foo", Signature::new().outputs(vec![Type::FieldElement]), 1)],
+            };
+
+            let p: TypedProg<FieldPrime> = TypedProg {
+                functions: vec![TypedFunction {
+                    id: "main",
+                    arguments: vec![
+                        Parameter::private(Variable::field_element("a".into())),
+                        Parameter {
+                            id: Variable::field_array("b".into(), 2),
+                            private: false,
+                        },
+                    ],
+                    statements: vec![
+                        TypedStatement::Definition(
+                            TypedAssignee::Identifier(Variable::field_element("c".into())),
+                            FieldElementExpression::Number(FieldPrime::from(0)).into(),
+                            None,
+                        ),
+                        TypedStatement::For(
+                            Variable::field_element("i".into()),
+                            FieldPrime::from(0),
+                            FieldPrime::from(2),
+                            vec![TypedStatement::Definition(
+                                TypedAssignee::Identifier(Variable::field_element("c".into())),
+                                FieldElementExpression::Add(
+                                    box FieldElementExpression::Identifier("c".into()),
+                                    box FieldElementExpression::Mult(
+                                        box FieldElementExpression::SelectConstant(
+                                            vec![FieldPrime::from(1), FieldPrime::from(2)],
+                                            box FieldElementExpression::Identifier("i".into()),
+                                        ),
+                                        box FieldElementExpression::Select(
+                                            box FieldElementArrayExpression::Identifier(
+                                                2,
+                                                "b".into(),
+                                            ),
+                                            box FieldElementExpression::Identifier("i".into()),
+                                        ),
+                                    ),
+                                )
+                                .into(),
+                                None,
+                            )],
+                            None,
+                        ),
+                        TypedStatement::Condition(
+                            FieldElementExpression::Identifier("a".into()).into(),
+                            FieldElementExpression::Identifier("c".into()).into(),
+                            Some(String::from("a is the dot product")),
+                            None,
+                        ),
+                        TypedStatement::Return(
+                            vec![FieldElementExpression::Add(
+                                box FieldElementExpression::Identifier(x),
+                                box FieldElementExpression::Number(FieldPrime::from(-1)),
+                            )
+                            .into()],
+                            None,
+                        ),
+                    ],
+                    signature: Signature::new()
+                        .inputs(vec![Type::FieldElement, Type::FieldElementArray(2)])
+                        .outputs(vec![Type::FieldElement]),
+                }],
+                imports: vec![Import::new_with_alias(
+                    String::from("./foo.code"),
+                    &String::from("foo"),
+                )],
+                imported_functions: vec![FlatFunction {
+                    id: String::from("foo"),
+                    arguments: vec![],
+                    statements: vec![FlatStatement::Return(FlatExpressionList {
+                        expressions: vec![FlatExpression::Number(FieldPrime::from(42))],
+                    })],
+                    signature: Signature::new().outputs(vec![Type::FieldElement]),
+                }],
+            };
+
+            let bytes = p.to_bytes();
+
+            assert_eq!(TypedProg::from_bytes(&bytes), Ok(p));
+        }
+    }
+}
@@ -2,8 +2,9 @@ use crate::absy;
 use crate::typed_absy::Variable;
 use std::fmt;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parameter<'ast> {
+    #[serde(borrow)]
     pub id: Variable<'ast>,
     pub private: bool,
 }
@@ -3,8 +3,9 @@ use crate::typed_absy::Identifier;
 use crate::types::Type;
 use std::fmt;
 
-#[derive(Clone, PartialEq, Hash, Eq)]
+#[derive(Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub struct Variable<'ast> {
+    #[serde(borrow)]
     pub id: Identifier<'ast>,
     pub _type: Type,
 }
@@ -19,7 +19,7 @@ fn use_variable(
 }
 
 pub fn split<T: Field>() -> FlatProg<T> {
-    let nbits = T::get_required_bits();
+    let nbits = T::bits();
 
     let mut counter = 0;
 
@@ -37,7 +37,7 @@ pub fn split<T: Field>() -> FlatProg<T> {
         format!("i0"),
         &mut counter,
     ))];
-    let directive_outputs: Vec<FlatVariable> = (0..T::get_required_bits())
+    let directive_outputs: Vec<FlatVariable> = (0..T::bits())
         .map(|index| use_variable(&mut layout, format!("o{}", index), &mut counter))
         .collect();
 
@@ -51,14 +51,14 @@ pub fn split<T: Field>() -> FlatProg<T> {
     let outputs = directive_outputs
         .iter()
         .enumerate()
-        .filter(|(index, _)| *index >= T::get_required_bits() - nbits)
+        .filter(|(index, _)| *index >= T::bits() - nbits)
         .map(|(_, o)| FlatExpression::Identifier(o.clone()))
         .collect();
 
     // o253, o252, ... o{253 - (nbits - 1)} are bits
     let mut statements: Vec<FlatStatement<T>> = (0..nbits)
         .map(|index| {
-            let bit = FlatExpression::Identifier(FlatVariable::new(T::get_required_bits() - index));
+            let bit = FlatExpression::Identifier(FlatVariable::new(T::bits() - index));
             FlatStatement::Condition(
                 bit.clone(),
                 FlatExpression::Mult(box bit.clone(), box bit.clone()),
@@ -73,7 +73,7 @@ pub fn split<T: Field>() -> FlatProg<T> {
         lhs_sum = FlatExpression::Add(
             box lhs_sum,
             box FlatExpression::Mult(
-                box FlatExpression::Identifier(FlatVariable::new(T::get_required_bits() - i)),
+                box FlatExpression::Identifier(FlatVariable::new(T::bits() - i)),
                 box FlatExpression::Number(T::from(2).pow(i)),
             ),
         );
@@ -212,12 +212,12 @@ mod tests {
             );
             assert_eq!(
                 unpack.statements.len(),
-                FieldPrime::get_required_bits() + 1 + 1 + 1
+                FieldPrime::bits() + 1 + 1 + 1
             ); // 128 bit checks, 1 directive, 1 sum check, 1 return
             assert_eq!(
                 unpack.statements[0],
                 FlatStatement::Directive(DirectiveStatement::new(
-                    (0..FieldPrime::get_required_bits())
+                    (0..FieldPrime::bits())
                         .map(|i| FlatVariable::new(i + 1))
                         .collect(),
                     Helper::bits(),
@@ -227,7 +227,7 @@ mod tests {
             assert_eq!(
                 *unpack.statements.last().unwrap(),
                 FlatStatement::Return(FlatExpressionList {
-                    expressions: (0..FieldPrime::get_required_bits())
+                    expressions: (0..FieldPrime::bits())
                         .map(|i| FlatExpression::Identifier(FlatVariable::new(i + 1)))
                         .collect()
                 })
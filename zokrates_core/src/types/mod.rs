@@ -4,7 +4,7 @@ use std::fmt;
 pub mod conversions;
 mod signature;
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Type {
     FieldElement,
     Boolean,
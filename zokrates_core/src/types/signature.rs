@@ -1,7 +1,7 @@
 use crate::types::Type;
 use std::fmt;
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Signature {
     pub inputs: Vec<Type>,
     pub outputs: Vec<Type>,
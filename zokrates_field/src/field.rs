@@ -11,6 +11,7 @@ use num_traits::{One, Zero};
 use pairing::bn256::Bn256;
 use pairing::ff::ScalarEngine;
 use pairing::Engine;
+use serde::Deserializer;
 use serde_derive::{Deserialize, Serialize};
 use std::convert::From;
 use std::fmt;
@@ -26,6 +27,16 @@ lazy_static! {
     .unwrap();
 }
 
+/// Error returned when a string does not represent an element of a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(String);
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid field element {}", self.0)
+    }
+}
+
 pub trait Pow<RHS> {
     type Output;
     fn pow(self, _: RHS) -> Self::Output;
@@ -42,6 +53,7 @@ pub trait Field:
     + Eq
     + Hash
     + PartialOrd
+    + Ord
     + Display
     + Debug
     + Add<Self, Output = Self>
@@ -78,26 +90,72 @@ pub trait Field:
     fn from_byte_vector(_: Vec<u8>) -> Self;
     /// Returns this `Field`'s contents as decimal string
     fn to_dec_string(&self) -> String;
+    /// Returns this `Field`'s contents as `0x` followed by big-endian hexadecimal digits, padded with zeros to
+    /// `2 * byte_width()` digits so that every element has the same length
+    fn to_hex_string(&self) -> String {
+        let mut bytes = self.into_byte_vector();
+        bytes.resize(Self::byte_width(), 0);
+        let digits: String = bytes.iter().rev().map(|b| format!("{:02x}", b)).collect();
+        format!("0x{}", digits)
+    }
+    /// Parses hexadecimal digits, optionally prefixed with `0x`, into an element of this `Field`. Values larger than
+    /// the field modulus are reduced, like in `try_from_dec_str`
+    fn from_hex_string(s: &str) -> Result<Self, ParseError>;
     /// Returns the multiplicative inverse, i.e.: self * self.inverse_mul() = Self::one()
     fn inverse_mul(&self) -> Self;
+    /// Returns the multiplicative inverse like `inverse_mul`, or `None` for zero which has none
+    fn inverse(&self) -> Option<Self> {
+        match self.is_zero() {
+            true => None,
+            false => Some(self.inverse_mul()),
+        }
+    }
     /// Returns the smallest value that can be represented by this field type.
     fn min_value() -> Self;
     /// Returns the largest value that can be represented by this field type.
     fn max_value() -> Self;
-    /// Returns the number of required bits to represent this field type.
-    fn get_required_bits() -> usize;
+    /// Returns the bit width of this field type, the number of bits of its largest element
+    fn bits() -> usize;
+    /// Returns the number of required bits to represent this field type, which is its bit width `bits()`
+    fn get_required_bits() -> usize {
+        Self::bits()
+    }
+    /// Returns the number of bytes used to serialize an element of this field type.
+    fn byte_width() -> usize {
+        (Self::bits() + 7) / 8
+    }
+    /// Returns the element of this `Field` representing a signed integer, i.e. `p - k` for `-k`
+    fn from_signed(value: i64) -> Self;
     /// Tries to parse a string into this representation
     fn try_from_dec_str<'a>(s: &'a str) -> Result<Self, ()>;
     /// Returns a decimal string representing a the member of the equivalence class of this `Field` in Z/pZ
     /// which lies in [-(p-1)/2, (p-1)/2]
     fn to_compact_dec_string(&self) -> String;
+    /// Returns whether this `Field` element is a quadratic residue, i.e. whether it has a square root
+    fn is_qr(&self) -> bool;
+    /// Returns the square root of this `Field` element if it is a quadratic residue, `None` otherwise.
+    /// The root returned is the smallest of the two roots `r` and `-r`
+    fn sqrt(&self) -> Option<Self>;
+    /// Returns the little-endian bit decomposition of this `Field` element, on `bits()` bits
+    fn to_bits_le(&self) -> Vec<bool>;
+    /// Returns this `Field` element raised to the integer value of `exp`, which lies in [0, p-1]. Unlike the `Pow`
+    /// implementations, which multiply `exp` times, this takes time logarithmic in `exp`. The exponent is not reduced
+    /// modulo p-1, the order of the multiplicative group, so `0.pow_mod(p-1)` is 0 while `0.pow_mod(0)` is 1.
+    fn pow_mod(&self, exp: &Self) -> Self;
 }
 
 #[derive(PartialEq, PartialOrd, Clone, Eq, Ord, Hash, Serialize, Deserialize)]
 pub struct FieldPrime {
+    #[serde(deserialize_with = "deserialize_reduced")]
     value: BigInt,
 }
 
+// values are reduced modulo p when deserialized, so that each element has a single, canonical representation
+fn deserialize_reduced<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+    let x: BigInt = serde::Deserialize::deserialize(deserializer)?;
+    Ok(&x - x.div_floor(&*P) * &*P)
+}
+
 impl Field for FieldPrime {
     type BellmanEngine = Bn256;
 
@@ -136,15 +194,36 @@ impl Field for FieldPrime {
             value: &*P - ToBigInt::to_bigint(&1).unwrap(),
         }
     }
-    fn get_required_bits() -> usize {
+    fn bits() -> usize {
         (*P).bits()
     }
+    fn from_signed(value: i64) -> FieldPrime {
+        let x = ToBigInt::to_bigint(&value).unwrap();
+        FieldPrime {
+            value: &x - x.div_floor(&*P) * &*P,
+        }
+    }
     fn try_from_dec_str<'a>(s: &'a str) -> Result<Self, ()> {
         let x = BigInt::parse_bytes(s.as_bytes(), 10).ok_or(())?;
         Ok(FieldPrime {
             value: &x - x.div_floor(&*P) * &*P,
         })
     }
+    fn from_hex_string(s: &str) -> Result<Self, ParseError> {
+        let digits = match s.starts_with("0x") {
+            true => &s[2..],
+            false => s,
+        };
+        // `parse_bytes` also accepts a sign and underscores, which are not hexadecimal digits
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(16)) {
+            return Err(ParseError(s.to_string()));
+        }
+        let x =
+            BigInt::parse_bytes(digits.as_bytes(), 16).ok_or_else(|| ParseError(s.to_string()))?;
+        Ok(FieldPrime {
+            value: &x - x.div_floor(&*P) * &*P,
+        })
+    }
     fn to_compact_dec_string(&self) -> String {
         // values up to (p-1)/2 included are represented as positive, values between (p+1)/2 and p-1 as represented as negative by subtracting p
         if self.value <= FieldPrime::max_value().value / 2 {
@@ -156,6 +235,73 @@ impl Field for FieldPrime {
             )
         }
     }
+    fn is_qr(&self) -> bool {
+        // Euler's criterion: a is a non-zero square iff a^((p-1)/2) == 1
+        self.value.is_zero()
+            || self.value.modpow(&((&*P - BigInt::one()) / 2), &*P) == BigInt::one()
+    }
+    fn sqrt(&self) -> Option<FieldPrime> {
+        if self.value.is_zero() {
+            return Some(FieldPrime::zero());
+        }
+        if !self.is_qr() {
+            return None;
+        }
+
+        // Tonelli-Shanks: write p - 1 = q * 2^s with q odd
+        let mut q = &*P - BigInt::one();
+        let mut s = 0;
+        while q.is_even() {
+            q = q / 2;
+            s += 1;
+        }
+
+        // find a quadratic non-residue z
+        let mut z = FieldPrime::from(2);
+        while z.is_qr() {
+            z = z + FieldPrime::one();
+        }
+
+        let mut m = s;
+        let mut c = z.value.modpow(&q, &*P);
+        let mut t = self.value.modpow(&q, &*P);
+        let mut r = self.value.modpow(&((&q + BigInt::one()) / 2), &*P);
+
+        while !t.is_one() {
+            // find the least i such that t^(2^i) == 1
+            let mut i = 0;
+            let mut t_pow = t.clone();
+            while !t_pow.is_one() {
+                t_pow = (&t_pow * &t_pow) % &*P;
+                i += 1;
+            }
+
+            let b = c.modpow(&(BigInt::one() << (m - i - 1)), &*P);
+            m = i;
+            c = (&b * &b) % &*P;
+            t = (&t * &c) % &*P;
+            r = (&r * &b) % &*P;
+        }
+
+        let minus_r = &*P - &r;
+        Some(FieldPrime {
+            value: std::cmp::min(r, minus_r),
+        })
+    }
+    fn pow_mod(&self, exp: &FieldPrime) -> FieldPrime {
+        FieldPrime {
+            value: self.value.modpow(&exp.value, &*P),
+        }
+    }
+    fn to_bits_le(&self) -> Vec<bool> {
+        let mut bits: Vec<bool> = self
+            .into_byte_vector()
+            .into_iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        bits.resize(Self::bits(), false);
+        bits
+    }
 }
 
 impl Default for FieldPrime {
@@ -612,6 +758,107 @@ mod tests {
             );
         }
 
+        #[test]
+        fn from_signed() {
+            assert_eq!(FieldPrime::from_signed(-1), FieldPrime::max_value());
+            assert_eq!(FieldPrime::from_signed(5), FieldPrime::from(5));
+            assert_eq!(
+                FieldPrime::from_signed(i64::min_value())
+                    + FieldPrime::from_signed(i64::max_value()),
+                FieldPrime::from_signed(-1)
+            );
+        }
+
+        #[test]
+        fn byte_width() {
+            assert_eq!(FieldPrime::byte_width(), 32);
+        }
+
+        #[test]
+        fn sqrt() {
+            let x = FieldPrime::from(16);
+            assert!(x.is_qr());
+            assert_eq!(x.sqrt(), Some(FieldPrime::from(4)));
+
+            // -1 is a square, check the root squares back to it
+            let x = FieldPrime::from(-1);
+            let root = x.sqrt().unwrap();
+            assert_eq!(root.clone() * root, x);
+
+            assert_eq!(FieldPrime::from(0).sqrt(), Some(FieldPrime::from(0)));
+        }
+
+        #[test]
+        fn inverse() {
+            let x = FieldPrime::from(7);
+            let inverse = x.inverse().unwrap();
+            assert_eq!(x * inverse, FieldPrime::one());
+        }
+
+        #[test]
+        fn inverse_zero() {
+            assert_eq!(FieldPrime::from(0).inverse(), None);
+        }
+
+        #[test]
+        fn pow_mod() {
+            assert_eq!(
+                FieldPrime::from(2).pow_mod(&FieldPrime::from(3)),
+                FieldPrime::from(8)
+            );
+            assert_eq!(
+                FieldPrime::from(2).pow_mod(&FieldPrime::from(300)),
+                FieldPrime::from(2).pow(300)
+            );
+            assert_eq!(
+                FieldPrime::from(0).pow_mod(&FieldPrime::from(0)),
+                FieldPrime::from(1)
+            );
+        }
+
+        #[test]
+        fn pow_mod_large_exponent() {
+            // x^(p-1) == 1 for every non-zero x, and the exponent is not reduced to 0
+            let order = FieldPrime::max_value();
+            assert_eq!(FieldPrime::from(3).pow_mod(&order), FieldPrime::from(1));
+            assert_eq!(FieldPrime::from(0).pow_mod(&order), FieldPrime::from(0));
+
+            // x^(p-2) is the inverse of x
+            let x = FieldPrime::from(7);
+            assert_eq!(
+                x.pow_mod(&(order - FieldPrime::from(1))),
+                x.inverse().unwrap()
+            );
+        }
+
+        #[test]
+        fn sqrt_non_residue() {
+            let x = FieldPrime::from(5);
+            assert!(!x.is_qr());
+            assert_eq!(x.sqrt(), None);
+        }
+
+        #[test]
+        fn bits() {
+            assert_eq!(FieldPrime::bits(), 254);
+            assert_eq!(FieldPrime::byte_width(), 32);
+        }
+
+        #[test]
+        fn to_bits_le() {
+            let bits = FieldPrime::from(5).to_bits_le();
+            assert_eq!(bits.len(), FieldPrime::bits());
+            assert_eq!(&bits[..4], &[true, false, true, false]);
+            assert!(bits[4..].iter().all(|b| !b));
+
+            // the largest element uses every bit
+            let bits = FieldPrime::max_value().to_bits_le();
+            assert_eq!(bits.len(), FieldPrime::bits());
+            assert!(bits[FieldPrime::bits() - 1]);
+
+            assert!(FieldPrime::from(0).to_bits_le().iter().all(|b| !b));
+        }
+
         #[test]
         fn serde_ser_deser() {
             let serialized = &serialize(&FieldPrime::from("11"), Infinite).unwrap();
@@ -619,6 +866,67 @@ mod tests {
             assert_eq!(FieldPrime::from("11"), deserialized);
         }
 
+        #[test]
+        fn serde_canonical() {
+            // a value which is not reduced deserializes to the reduced element, and serializes like it
+            let unreduced = FieldPrime {
+                value: &*P + BigInt::from(11),
+            };
+            let serialized = &serialize(&unreduced, Infinite).unwrap();
+            let deserialized: FieldPrime = deserialize(serialized).unwrap();
+            assert_eq!(deserialized, FieldPrime::from("11"));
+            assert_eq!(
+                serialize(&deserialized, Infinite).unwrap(),
+                serialize(&FieldPrime::from("11"), Infinite).unwrap()
+            );
+        }
+
+        #[test]
+        fn hex_string() {
+            assert_eq!(
+                FieldPrime::from(255).to_hex_string(),
+                format!("0x{}ff", "0".repeat(62))
+            );
+
+            let max =
+                "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+
+            for value in &["0", "1", "255", "4660", max] {
+                let fp = FieldPrime::from(*value);
+                let hex = fp.to_hex_string();
+                assert_eq!(hex.len(), 2 + 2 * FieldPrime::byte_width());
+                assert_eq!(FieldPrime::from_hex_string(&hex), Ok(fp.clone()));
+                assert_eq!(FieldPrime::from_hex_string(&hex[2..]), Ok(fp));
+            }
+
+            // digits need not be padded
+            assert_eq!(
+                FieldPrime::from_hex_string("0x1234"),
+                Ok(FieldPrime::from(4660))
+            );
+            assert_eq!(
+                FieldPrime::from_hex_string("abc"),
+                Ok(FieldPrime::from(2748))
+            );
+
+            // values larger than the modulus are reduced
+            let p_plus_11 = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f000000c";
+            assert_eq!(
+                FieldPrime::from_hex_string(p_plus_11),
+                Ok(FieldPrime::from(11))
+            );
+        }
+
+        #[test]
+        fn hex_string_invalid() {
+            for s in &["", "0x", "0x12g4", "-1", "0x0x1"] {
+                assert_eq!(
+                    FieldPrime::from_hex_string(s),
+                    Err(ParseError(s.to_string()))
+                );
+            }
+        }
+
         #[test]
         fn serde_json_ser_deser() {
             let serialized = serde_json::to_string(&FieldPrime::from("11")).unwrap();
@@ -798,5 +1106,4 @@ mod tests {
             assert_eq!(FieldPrime::from_bellman(a), cc);
         }
     }
-
 }
@@ -142,5 +142,142 @@ mod tests {
             let parse = ZoKratesParser::parse(Rule::iteration_statement, input);
             assert!(parse.is_ok());
         }
+
+        #[test]
+        fn parse_assertion_with_message() {
+            let input = "assert(a == b, \"balance mismatch\")";
+
+            let parse = ZoKratesParser::parse(Rule::expression_statement, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_cast() {
+            parses_to! {
+                parser: ZoKratesParser,
+                input: "bool(a)",
+                rule: Rule::expression,
+                tokens: [
+                    expression(0, 7, [
+                        term(0, 7, [
+                            cast_expression(0, 7, [
+                                ty_basic(0, 4, [
+                                    ty_bool(0, 4)
+                                ]),
+                                expression(5, 6, [
+                                    term(5, 6, [
+                                        primary_expression(5, 6, [
+                                            identifier(5, 6)
+                                        ])
+                                    ])
+                                ])
+                            ])
+                        ])
+                    ])
+                ]
+            };
+        }
+
+        #[test]
+        fn parse_concat() {
+            parses_to! {
+                parser: ZoKratesParser,
+                input: "a ++ b",
+                rule: Rule::expression,
+                tokens: [
+                    expression(0, 6, [
+                        term(0, 1, [
+                            primary_expression(0, 1, [
+                                identifier(0, 1)
+                            ])
+                        ]),
+                        op_concat(2, 4),
+                        term(5, 6, [
+                            primary_expression(5, 6, [
+                                identifier(5, 6)
+                            ])
+                        ])
+                    ])
+                ]
+            };
+        }
+
+        #[test]
+        fn parse_max() {
+            parses_to! {
+                parser: ZoKratesParser,
+                input: "max(a, 1)",
+                rule: Rule::expression,
+                tokens: [
+                    expression(0, 9, [
+                        term(0, 9, [
+                            builtin_expression(0, 9, [
+                                builtin(0, 3, [
+                                    builtin_max(0, 3)
+                                ]),
+                                expression(4, 5, [
+                                    term(4, 5, [
+                                        primary_expression(4, 5, [
+                                            identifier(4, 5)
+                                        ])
+                                    ])
+                                ]),
+                                expression(7, 8, [
+                                    term(7, 8, [
+                                        primary_expression(7, 8, [
+                                            constant(7, 8)
+                                        ])
+                                    ])
+                                ])
+                            ])
+                        ])
+                    ])
+                ]
+            };
+        }
+
+        #[test]
+        fn parse_invalid_identifier_because_builtin() {
+            fails_with! {
+                parser: ZoKratesParser,
+                input: "min",
+                rule: Rule::identifier,
+                positives: vec![Rule::identifier],
+                negatives: vec![],
+                pos: 0
+            };
+        }
+
+        #[test]
+        fn parse_slice() {
+            parses_to! {
+                parser: ZoKratesParser,
+                input: "a[1..3]",
+                rule: Rule::postfix_expression,
+                tokens: [
+                    postfix_expression(0, 7, [
+                        identifier(0, 1),
+                        access(1, 7, [
+                            slice_access(1, 7, [
+                                expression(2, 3, [
+                                    term(2, 3, [
+                                        primary_expression(2, 3, [
+                                            constant(2, 3)
+                                        ])
+                                    ])
+                                ]),
+                                expression(5, 6, [
+                                    term(5, 6, [
+                                        primary_expression(5, 6, [
+                                            constant(5, 6)
+                                        ])
+                                    ])
+                                ])
+                            ])
+                        ])
+                    ])
+                ]
+            };
+        }
     }
 }
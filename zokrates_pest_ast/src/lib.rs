@@ -8,12 +8,13 @@ use zokrates_parser::Rule;
 extern crate lazy_static;
 
 pub use ast::{
-    Access, ArrayAccess, ArrayType, AssertionStatement, Assignee, AssignmentStatement, BasicType,
-    BinaryExpression, BinaryOperator, CallAccess, ConstantExpression, DefinitionStatement,
-    Expression, File, Function, IdentifierExpression, ImportDirective, ImportSource,
-    InlineArrayExpression, IterationStatement, MultiAssignmentStatement, Parameter,
-    PostfixExpression, ReturnStatement, Span, Statement, TernaryExpression, Type, UnaryExpression,
-    UnaryOperator, Visibility,
+    Access, ArrayAccess, ArrayType, AssertionMessage, AssertionStatement, Assignee,
+    AssignmentStatement, BasicType, BinaryExpression, BinaryOperator, Builtin, BuiltinExpression,
+    CallAccess, CastExpression, ConstantExpression, DefinitionStatement, Expression, File,
+    Function, IdentifierExpression, ImportDirective, ImportSource, InlineArrayExpression,
+    IterationStatement, MultiAssignmentStatement, Parameter, PostfixExpression, ReturnStatement,
+    SliceAccess, Span, Statement, TernaryExpression, Type, UnaryExpression, UnaryOperator,
+    Visibility,
 };
 
 mod ast {
@@ -41,6 +42,7 @@ mod ast {
                 | Operator::new(Rule::op_gte, Assoc::Left)
                 | Operator::new(Rule::op_lt, Assoc::Left)
                 | Operator::new(Rule::op_gt, Assoc::Left),
+            Operator::new(Rule::op_concat, Assoc::Left),
             Operator::new(Rule::op_add, Assoc::Left) | Operator::new(Rule::op_sub, Assoc::Left),
             Operator::new(Rule::op_mul, Assoc::Left) | Operator::new(Rule::op_div, Assoc::Left),
             Operator::new(Rule::op_pow, Assoc::Left),
@@ -65,6 +67,7 @@ mod ast {
             Rule::op_mul => Expression::binary(BinaryOperator::Mul, lhs, rhs, span),
             Rule::op_div => Expression::binary(BinaryOperator::Div, lhs, rhs, span),
             Rule::op_pow => Expression::binary(BinaryOperator::Pow, lhs, rhs, span),
+            Rule::op_concat => Expression::binary(BinaryOperator::Concat, lhs, rhs, span),
             Rule::op_equal => Expression::binary(BinaryOperator::Eq, lhs, rhs, span),
             Rule::op_not_equal => Expression::binary(BinaryOperator::NotEq, lhs, rhs, span),
             Rule::op_lte => Expression::binary(BinaryOperator::Lte, lhs, rhs, span),
@@ -98,6 +101,12 @@ mod ast {
                     Rule::conditional_expression => Expression::Ternary(
                         TernaryExpression::from_pest(&mut pair.into_inner()).unwrap(),
                     ),
+                    Rule::cast_expression => Expression::Cast(
+                        CastExpression::from_pest(&mut pair.into_inner()).unwrap(),
+                    ),
+                    Rule::builtin_expression => Expression::Builtin(
+                        BuiltinExpression::from_pest(&mut pair.into_inner()).unwrap(),
+                    ),
                     Rule::primary_expression => {
                         // maybe this could be simplified
                         let next = next.into_inner().next().unwrap();
@@ -137,7 +146,7 @@ mod ast {
                             span
                         })
                     },
-                    r => unreachable!("`term` should contain one of [`expression`, `conditional_expression`, `primary_expression`, `postfix_expression`, `inline_array_expression`, `unary_expression`], found {:#?}", r)
+                    r => unreachable!("`term` should contain one of [`expression`, `conditional_expression`, `cast_expression`, `builtin_expression`, `primary_expression`, `postfix_expression`, `inline_array_expression`, `unary_expression`], found {:#?}", r)
                 }
             }
             r => unreachable!(
@@ -279,6 +288,16 @@ mod ast {
     #[pest_ast(rule(Rule::expression_statement))]
     pub struct AssertionStatement<'ast> {
         pub expression: Expression<'ast>,
+        pub message: Option<AssertionMessage<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::assertion_message))]
+    pub struct AssertionMessage<'ast> {
+        #[pest_ast(outer(with(span_into_str)))]
+        pub value: String,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
@@ -329,6 +348,7 @@ mod ast {
         Lte,
         Gte,
         Pow,
+        Concat,
     }
 
     #[derive(Debug, PartialEq, FromPest, Clone)]
@@ -344,6 +364,27 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, PartialEq, FromPest, Clone)]
+    #[pest_ast(rule(Rule::builtin))]
+    pub enum Builtin<'ast> {
+        Max(Max<'ast>),
+        Min(Min<'ast>),
+    }
+
+    #[derive(Debug, PartialEq, FromPest, Clone)]
+    #[pest_ast(rule(Rule::builtin_max))]
+    pub struct Max<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, PartialEq, FromPest, Clone)]
+    #[pest_ast(rule(Rule::builtin_min))]
+    pub struct Min<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, PartialEq, Clone)]
     pub enum Expression<'ast> {
         Ternary(TernaryExpression<'ast>),
@@ -353,6 +394,8 @@ mod ast {
         Constant(ConstantExpression<'ast>),
         InlineArray(InlineArrayExpression<'ast>),
         Unary(UnaryExpression<'ast>),
+        Cast(CastExpression<'ast>),
+        Builtin(BuiltinExpression<'ast>),
     }
 
     #[derive(Debug, FromPest, PartialEq, Clone)]
@@ -373,6 +416,25 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::cast_expression))]
+    pub struct CastExpression<'ast> {
+        pub ty: BasicType<'ast>,
+        pub expression: Box<Expression<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::builtin_expression))]
+    pub struct BuiltinExpression<'ast> {
+        pub builtin: Builtin<'ast>,
+        pub left: Box<Expression<'ast>>,
+        pub right: Box<Expression<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::inline_array_expression))]
     pub struct InlineArrayExpression<'ast> {
@@ -395,6 +457,7 @@ mod ast {
     pub enum Access<'ast> {
         Call(CallAccess<'ast>),
         Select(ArrayAccess<'ast>),
+        Slice(SliceAccess<'ast>),
     }
 
     #[derive(Debug, FromPest, PartialEq, Clone)]
@@ -413,6 +476,15 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::slice_access))]
+    pub struct SliceAccess<'ast> {
+        pub from: Expression<'ast>,
+        pub to: Expression<'ast>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, PartialEq, Clone)]
     pub struct BinaryExpression<'ast> {
         pub op: BinaryOperator,
@@ -469,6 +541,8 @@ mod ast {
                 Expression::Postfix(p) => &p.span,
                 Expression::InlineArray(a) => &a.span,
                 Expression::Unary(u) => &u.span,
+                Expression::Cast(c) => &c.span,
+                Expression::Builtin(b) => &b.span,
             }
         }
     }